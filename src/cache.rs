@@ -0,0 +1,175 @@
+//! A persistent, on-disk cache of API responses.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cassette::request_key_with_origin;
+use crate::client::{ApiRequest, Endpoint};
+
+/// How long search results are cached by default; the catalog changes slowly.
+pub const DEFAULT_SEARCH_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long enrollment packages are cached by default; seat counts change quickly.
+pub const DEFAULT_SECTIONS_TTL: Duration = Duration::from_secs(2 * 60);
+
+/// Caches response bodies under a directory, keyed by request and host (unlike a cassette, a
+/// cache shouldn't answer for one host with another's responses), with a time-to-live per
+/// [`Endpoint`].
+#[derive(Debug)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttls: HashMap<Endpoint, Duration>,
+    refresh: bool,
+    counters: Counters,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    expired: AtomicU64,
+    writes: AtomicU64,
+}
+
+/// How a cache has been used so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    /// Lookups that found nothing usable, including `expired` ones.
+    pub misses: u64,
+    pub expired: u64,
+    pub writes: u64,
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({} expired), {} writes",
+            self.hits, self.misses, self.expired, self.writes
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    /// Seconds since the Unix epoch.
+    stored_at: u64,
+    url: String,
+    body: String,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttls: HashMap::from([
+                (Endpoint::Search, DEFAULT_SEARCH_TTL),
+                (Endpoint::Sections, DEFAULT_SECTIONS_TTL),
+            ]),
+            refresh: false,
+            counters: Counters::default(),
+        }
+    }
+
+    /// Sets how long responses from `endpoint` stay fresh.
+    pub fn ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    /// When set, cached responses are never read, only overwritten with fresh ones.
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn ttl_for(&self, endpoint: Endpoint) -> Duration {
+        self.ttls.get(&endpoint).copied().unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            expired: self.counters.expired.load(Ordering::Relaxed),
+            writes: self.counters.writes.load(Ordering::Relaxed),
+        }
+    }
+
+    fn path_for(&self, request: &ApiRequest) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", request_key_with_origin(request)))
+    }
+
+    /// Returns the cached body answering `request`, if there's a fresh one.
+    pub fn get(&self, request: &ApiRequest) -> Option<String> {
+        if self.refresh {
+            self.counters.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        let entry = fs::read_to_string(self.path_for(request))
+            .ok()
+            .and_then(|s| serde_json::from_str::<CacheEntry>(&s).ok());
+        let entry = match entry {
+            Some(entry) => entry,
+            None => {
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        };
+
+        let age = now_secs().saturating_sub(entry.stored_at);
+        if age >= self.ttl_for(request.endpoint).as_secs() {
+            self.counters.misses.fetch_add(1, Ordering::Relaxed);
+            self.counters.expired.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        log::debug!("cache hit for {} ({}s old)", request.url, age);
+        self.counters.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entry.body)
+    }
+
+    /// Caches `body` as the answer to `request`.
+    ///
+    /// Failing to write is logged rather than returned; a broken cache shouldn't fail a request.
+    pub fn put(&self, request: &ApiRequest, body: &str) {
+        let entry = CacheEntry {
+            stored_at: now_secs(),
+            url: request.url.clone(),
+            body: body.to_string(),
+        };
+        let written = fs::create_dir_all(&self.dir).and_then(|_| {
+            fs::write(
+                self.path_for(request),
+                serde_json::to_string(&entry).expect("couldn't convert to string"),
+            )
+        });
+
+        match written {
+            Ok(()) => {
+                self.counters.writes.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => log::warn!("couldn't cache the response from {}: {}", request.url, e),
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
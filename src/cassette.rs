@@ -0,0 +1,153 @@
+//! Recording API responses to disk and replaying them without a network.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::client::ApiRequest;
+use crate::error::MadpingerError;
+
+/// Whether a [`Cassette`] saves responses or serves them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Every successful response body is saved.
+    Record,
+    /// Responses are served from disk; nothing is sent over the network.
+    Replay,
+}
+
+/// A directory of raw response bodies, keyed by request.
+///
+/// Each response is stored as `<key>.json` (the body exactly as the API sent it), next to a
+/// `<key>.request.json` describing the request it answers; see [`request_key`].
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    dir: PathBuf,
+    mode: CassetteMode,
+}
+
+/// The request a recorded body answers, for humans browsing a cassette.
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    payload: Option<String>,
+}
+
+impl Cassette {
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Record,
+        }
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Replay,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Where the body answering `request` is (or would be) stored.
+    pub fn path_for(&self, request: &ApiRequest) -> PathBuf {
+        self.dir.join(format!("{}.json", request_key(request)))
+    }
+
+    /// Reads the recorded body answering `request`.
+    pub fn load(&self, request: &ApiRequest) -> Result<String, MadpingerError> {
+        let path = self.path_for(request);
+        if !path.is_file() {
+            return Err(MadpingerError::CassetteMiss {
+                method: request.method.to_string(),
+                url: request.url.clone(),
+                path,
+            });
+        }
+        Ok(fs::read_to_string(path)?)
+    }
+
+    /// Saves `body` as the answer to `request`.
+    pub fn store(&self, request: &ApiRequest, body: &str) -> Result<(), MadpingerError> {
+        fs::create_dir_all(&self.dir)?;
+
+        let recorded = RecordedRequest {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+            payload: request.payload.clone(),
+        };
+        let path = self.path_for(request);
+        fs::write(
+            path.with_extension("request.json"),
+            serde_json::to_string_pretty(&recorded).expect("couldn't convert to string"),
+        )?;
+        fs::write(path, body)?;
+        Ok(())
+    }
+}
+
+/// A stable, file-name-safe key for a request, derived from its method, path (with the query)
+/// and payload.
+///
+/// The host is left out, so a cassette recorded against one base URL replays against another
+/// (e.g. a mock server or a proxy). Looks like `sections-9f86d081884c7d65`.
+pub fn request_key(request: &ApiRequest) -> String {
+    format!("{}-{:016x}", request.endpoint, hash_request(request, false))
+}
+
+/// Like [`request_key`], but telling apart the same request sent to different hosts.
+pub(crate) fn request_key_with_origin(request: &ApiRequest) -> String {
+    format!("{}-{:016x}", request.endpoint, hash_request(request, true))
+}
+
+fn hash_request(request: &ApiRequest, with_origin: bool) -> u64 {
+    let (origin, path) = match Url::parse(&request.url) {
+        Ok(url) => {
+            let query = url.query().map(|q| format!("?{}", q)).unwrap_or_default();
+            (
+                url.origin().ascii_serialization(),
+                format!("{}{}", url.path(), query),
+            )
+        }
+        Err(_) => (String::new(), request.url.clone()),
+    };
+
+    let mut hash = Fnv1a::default();
+    hash.write(request.method.as_str().as_bytes());
+    hash.write(b"\n");
+    if with_origin {
+        hash.write(origin.as_bytes());
+    }
+    hash.write(path.as_bytes());
+    hash.write(b"\n");
+    hash.write(request.payload.as_deref().unwrap_or_default().as_bytes());
+    hash.0
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher`, its output is stable across Rust releases.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
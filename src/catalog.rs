@@ -0,0 +1,347 @@
+//! Searching a catalog snapshot saved to disk, without the network.
+//!
+//! A [`Catalog`] is every course of a term as the search endpoint returns it, optionally with each
+//! course's enrollment packages. A [`CatalogIndex`] is an inverted index over it that answers
+//! [`SearchQuery`]s the way the API would, ranking courses by how well they match.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::client::CseClient;
+use crate::error::{decode, MadpingerError};
+use crate::search::query::SearchQuery;
+use crate::search::schema::{SearchApiPing, SearchedCourse};
+use crate::search::search_all;
+use crate::section::filter::PackageFilter;
+use crate::section::schema::CourseSection;
+use crate::section::{get_sections_bulk, CourseKey};
+use crate::CourseStatusFilters;
+
+/// How many courses are asked for per page while downloading a catalog.
+const DOWNLOAD_PAGE_SIZE: usize = 50;
+
+/// A course in a [`Catalog`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub course: SearchedCourse,
+    /// Names of the people teaching any package of the course; empty unless downloaded.
+    #[serde(default)]
+    pub instructors: Vec<String>,
+    /// The course's enrollment packages; `None` unless downloaded.
+    #[serde(default)]
+    pub packages: Option<Vec<CourseSection>>,
+}
+
+/// Every course of a term, saved to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Catalog {
+    pub term_code: String,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    pub entries: Vec<CatalogEntry>,
+}
+
+impl Catalog {
+    pub fn new(term_code: impl Into<String>, entries: Vec<CatalogEntry>) -> Self {
+        Self {
+            term_code: term_code.into(),
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            entries,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MadpingerError> {
+        let path = path.as_ref();
+        decode(&path.display().to_string(), &fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MadpingerError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            path,
+            serde_json::to_string(self).expect("couldn't convert to string"),
+        )?;
+        Ok(())
+    }
+
+    /// Whether packages were downloaded with the catalog, so package filters can be checked.
+    pub fn has_packages(&self) -> bool {
+        self.entries.iter().any(|entry| entry.packages.is_some())
+    }
+
+    /// Builds an index to search the catalog with.
+    pub fn index(&self) -> CatalogIndex<'_> {
+        CatalogIndex::build(self)
+    }
+}
+
+/// Downloads every course of a term into a catalog.
+///
+/// With `with_packages`, the packages of every course are fetched too (at most `concurrency`
+/// courses at once), to learn who teaches it and to filter on them offline; that's a request per
+/// course, so it's much slower. A course whose packages can't be fetched is saved without them.
+pub async fn download(
+    client: &CseClient,
+    term_code: &str,
+    with_packages: bool,
+    concurrency: usize,
+) -> Result<Catalog, MadpingerError> {
+    let query = SearchQuery::new(term_code)
+        .statuses(CourseStatusFilters::new(false, false, false))
+        .page_size(DOWNLOAD_PAGE_SIZE);
+    let courses: Vec<SearchedCourse> = search_all(client, query, None).try_collect().await?;
+
+    let mut fetched: HashMap<CourseKey, Vec<CourseSection>> = HashMap::new();
+    if with_packages {
+        let keys: Vec<CourseKey> = courses.iter().map(CourseKey::from).collect();
+        let mut results = get_sections_bulk(client, keys, concurrency);
+        while let Some((key, packages)) = results.next().await {
+            match packages {
+                Ok(packages) => {
+                    fetched.insert(key, packages);
+                }
+                Err(e) => log::warn!(
+                    "saving {} without its packages: couldn't fetch them ({})",
+                    key,
+                    e
+                ),
+            }
+        }
+    }
+
+    let entries = courses
+        .into_iter()
+        .map(|course| {
+            let packages = fetched.remove(&CourseKey::from(&course));
+            CatalogEntry {
+                instructors: packages.as_deref().map(instructors).unwrap_or_default(),
+                packages,
+                course,
+            }
+        })
+        .collect();
+    Ok(Catalog::new(term_code, entries))
+}
+
+/// Names of the people teaching any of `packages`, sorted.
+fn instructors(packages: &[CourseSection]) -> Vec<String> {
+    let mut names: Vec<String> = packages
+        .iter()
+        .flat_map(|package| &package.sections)
+        .flat_map(|section| &section.instructors)
+        .filter_map(|person| {
+            let name = &person.name;
+            match (&name.first, &name.last) {
+                (Some(first), Some(last)) => Some(format!("{} {}", first, last)),
+                (None, Some(last)) => Some(last.clone()),
+                _ => None,
+            }
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Where a term appears in one course, and how much that counts for.
+#[derive(Debug, Clone, Copy)]
+struct Posting {
+    entry: usize,
+    weight: f64,
+}
+
+/// How much a term counts for, depending on the field it's found in.
+const DESIGNATION_WEIGHT: f64 = 4.0;
+const TITLE_WEIGHT: f64 = 3.0;
+const TOPIC_WEIGHT: f64 = 2.0;
+const INSTRUCTOR_WEIGHT: f64 = 2.0;
+const KEYWORD_WEIGHT: f64 = 1.5;
+const DESCRIPTION_WEIGHT: f64 = 1.0;
+/// How much a term counts for when it only starts a word, e.g. `prog` in `programming`.
+const PREFIX_FACTOR: f64 = 0.5;
+
+/// An inverted index over a [`Catalog`].
+///
+/// Every query term has to match (a whole word, or the start of one) in a course's designations,
+/// title, description, topics, instructors or requirement keywords. Courses are ranked by the
+/// sum of their terms' field weights, each scaled by how rare the term is across the catalog.
+#[derive(Debug)]
+pub struct CatalogIndex<'a> {
+    catalog: &'a Catalog,
+    terms: BTreeMap<String, Vec<Posting>>,
+}
+
+impl<'a> CatalogIndex<'a> {
+    pub fn build(catalog: &'a Catalog) -> Self {
+        let mut terms: BTreeMap<String, HashMap<usize, f64>> = BTreeMap::new();
+        let mut add = |entry: usize, text: &str, weight: f64| {
+            for token in tokenize(text) {
+                *terms.entry(token).or_default().entry(entry).or_default() += weight;
+            }
+        };
+
+        for (
+            i,
+            CatalogEntry {
+                course,
+                instructors,
+                ..
+            },
+        ) in catalog.entries.iter().enumerate()
+        {
+            add(i, &course.course_designation, DESIGNATION_WEIGHT);
+            add(i, &course.full_course_designation, DESIGNATION_WEIGHT);
+            for designation in course.cross_listed_designations() {
+                add(i, &designation, DESIGNATION_WEIGHT);
+            }
+            add(i, &course.title, TITLE_WEIGHT);
+            add(i, &course.description, DESCRIPTION_WEIGHT);
+            for topic in &course.topics {
+                add(i, &topic.short_description, TOPIC_WEIGHT);
+                add(i, &topic.long_description, DESCRIPTION_WEIGHT);
+            }
+            for instructor in instructors {
+                add(i, instructor, INSTRUCTOR_WEIGHT);
+            }
+            let requirements = course
+                .breadths
+                .iter()
+                .chain(&course.levels)
+                .chain(&course.general_ed)
+                .chain(&course.ethnic_studies);
+            for requirement in requirements {
+                add(i, &requirement.description, KEYWORD_WEIGHT);
+            }
+        }
+
+        let terms = terms
+            .into_iter()
+            .map(|(term, postings)| {
+                let mut postings: Vec<Posting> = postings
+                    .into_iter()
+                    .map(|(entry, weight)| Posting { entry, weight })
+                    .collect();
+                postings.sort_by_key(|p| p.entry);
+                (term, postings)
+            })
+            .collect();
+        Self { catalog, terms }
+    }
+
+    pub fn catalog(&self) -> &'a Catalog {
+        self.catalog
+    }
+
+    /// Every course matching `query` (its query string, the filters checked by
+    /// [`SearchQuery::matches`], and its statuses and modes) with a package passing `packages`,
+    /// best match first unless the query asks for another order.
+    ///
+    /// Like a search against the API, a package has to pass every package filter at once, and
+    /// courses saved without packages never do. If the catalog has no packages at all, package
+    /// filters can't be checked and are ignored.
+    pub fn search(&self, query: &SearchQuery, packages: &PackageFilter) -> Vec<&'a SearchedCourse> {
+        let scores = self.score(&query.query);
+
+        let mut ranked: Vec<(usize, f64)> = match scores {
+            Some(scores) => scores.into_iter().collect(),
+            None => (0..self.catalog.entries.len()).map(|i| (i, 0.0)).collect(),
+        };
+        let check_packages = self.catalog.has_packages();
+        ranked.retain(|(i, _)| {
+            let entry = &self.catalog.entries[*i];
+            query.matches(&entry.course)
+                && (!check_packages
+                    || entry
+                        .packages
+                        .iter()
+                        .flatten()
+                        .any(|package| query.matches_package(package, packages)))
+        });
+        ranked.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then(a.cmp(b)));
+
+        // Relevance is what the ranking is; any other order is applied on top of it.
+        let mut courses: Vec<&SearchedCourse> = ranked
+            .into_iter()
+            .map(|(i, _)| &self.catalog.entries[i].course)
+            .collect();
+        query.sort_order.sort(&mut courses);
+        courses
+    }
+
+    /// Answers `query` with a single page of results, like the search endpoint does.
+    pub fn search_page(&self, query: &SearchQuery, packages: &PackageFilter) -> SearchApiPing {
+        let courses = self.search(query, packages);
+        let found = courses.len();
+        let hits = courses
+            .into_iter()
+            .skip((query.page.max(1) - 1) * query.page_size)
+            .take(query.page_size)
+            .cloned()
+            .collect();
+        SearchApiPing { found, hits }
+    }
+
+    /// Scores the courses matching every term of a query string; `None` matches everything.
+    fn score(&self, query: &str) -> Option<HashMap<usize, f64>> {
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() {
+            return None;
+        }
+
+        let total = self.catalog.entries.len() as f64;
+        let mut scores: Option<HashMap<usize, f64>> = None;
+        for term in query_terms {
+            let mut term_scores: HashMap<usize, f64> = HashMap::new();
+            let mut matched: HashSet<usize> = HashSet::new();
+            let candidates = self
+                .terms
+                .range(term.clone()..)
+                .take_while(|(t, _)| t.starts_with(&term));
+            let mut weighted: Vec<(&Vec<Posting>, f64)> = Vec::new();
+            for (token, postings) in candidates {
+                let factor = if *token == term { 1.0 } else { PREFIX_FACTOR };
+                matched.extend(postings.iter().map(|p| p.entry));
+                weighted.push((postings, factor));
+            }
+
+            let idf = (1.0 + total / (matched.len().max(1) as f64)).ln();
+            for (postings, factor) in weighted {
+                for posting in postings {
+                    *term_scores.entry(posting.entry).or_default() += posting.weight * factor * idf;
+                }
+            }
+
+            scores = Some(match scores {
+                None => term_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(entry, score)| {
+                        term_scores.get(&entry).map(|s| (entry, score + s))
+                    })
+                    .collect(),
+            });
+        }
+        scores
+    }
+}
+
+/// Lowercases text and splits it into words of letters and digits.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,467 @@
+//! A reusable client for talking to the CS&E API.
+
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::Stream;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, CONNECTION,
+    CONTENT_TYPE,
+};
+use reqwest::Error as ReqwestError;
+use reqwest::{Client, Method};
+
+use crate::cache::ResponseCache;
+use crate::cassette::{Cassette, CassetteMode};
+use crate::error::{truncate, MadpingerError};
+use crate::rate_limit::RateLimiter;
+use crate::retry::{retry_after, RetryPolicy};
+use crate::search::query::{SearchQuery, SubjectFilter};
+use crate::search::schema::SearchApiPing;
+use crate::search::schema::SearchedCourse;
+use crate::search::{
+    get_search_info, resolve_subjects, retain_with_packages, run_query, search_all,
+    SEARCH_POST_PATH,
+};
+use crate::section::filter::PackageFilter;
+use crate::section::schema::CourseSection;
+use crate::section::{get_section_info, get_sections_bulk, CourseKey, SECTION_GET_PATH};
+use crate::{default_client_headers, CourseStatusFilters};
+
+/// The base URL of the public CS&E site.
+pub const DEFAULT_BASE_URL: &str = "https://public.enroll.wisc.edu";
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The API endpoints a [`CseClient`] talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// Course search (`POST`).
+    Search,
+    /// The enrollment packages of a course (`GET`).
+    Sections,
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Search => write!(f, "search"),
+            Endpoint::Sections => write!(f, "sections"),
+        }
+    }
+}
+
+/// A single request to the API.
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub endpoint: Endpoint,
+    pub method: Method,
+    pub url: String,
+    /// The request body, if any.
+    pub payload: Option<String>,
+}
+
+impl ApiRequest {
+    pub fn get(endpoint: Endpoint, url: String) -> Self {
+        Self {
+            endpoint,
+            method: Method::GET,
+            url,
+            payload: None,
+        }
+    }
+
+    pub fn post(endpoint: Endpoint, url: String, payload: String) -> Self {
+        Self {
+            endpoint,
+            method: Method::POST,
+            url,
+            payload: Some(payload),
+        }
+    }
+}
+
+/// Receives the raw body of every response a [`CseClient`] hands out,
+/// whether it came from the network or a replayed [`Cassette`].
+///
+/// Closures taking `(&ApiRequest, &str)` are observers too.
+pub trait ResponseObserver: Send + Sync {
+    fn on_response(&self, request: &ApiRequest, body: &str);
+}
+
+impl<F> ResponseObserver for F
+where
+    F: Fn(&ApiRequest, &str) + Send + Sync,
+{
+    fn on_response(&self, request: &ApiRequest, body: &str) {
+        self(request, body)
+    }
+}
+
+#[derive(Clone, Default)]
+struct Observers(Vec<Arc<dyn ResponseObserver>>);
+
+impl std::fmt::Debug for Observers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} observer(s)]", self.0.len())
+    }
+}
+
+/// A client for the CS&E API.
+///
+/// Owns the underlying HTTP client (headers, cookies, timeouts) and the base URL that every
+/// endpoint is resolved against, so it can be pointed at a local stand-in server or a proxy.
+/// Cloning is cheap; clones share the same connection pool.
+#[derive(Debug, Clone)]
+pub struct CseClient {
+    http: Client,
+    base_url: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cassette: Option<Cassette>,
+    cache: Option<Arc<ResponseCache>>,
+    observers: Observers,
+}
+
+impl CseClient {
+    /// Builds a client with the default settings, pointed at [`DEFAULT_BASE_URL`].
+    pub fn new() -> Result<Self, ReqwestError> {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> CseClientBuilder {
+        CseClientBuilder::default()
+    }
+
+    /// The underlying HTTP client.
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    /// The base URL endpoints are resolved against (without a trailing slash).
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// The rate limiter every request waits on, if any.
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// The cassette responses are recorded to or replayed from, if any.
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_ref()
+    }
+
+    /// The cache responses are read from and written to, if any.
+    pub fn cache(&self) -> Option<&Arc<ResponseCache>> {
+        self.cache.as_ref()
+    }
+
+    /// The URL search requests are posted to.
+    pub fn search_url(&self) -> String {
+        format!("{}{}", self.base_url, SEARCH_POST_PATH)
+    }
+
+    /// The URL the sections (enrollment packages) of a course are fetched from.
+    pub fn sections_url(&self, term_code: &str, subject_code: &str, course_id: &str) -> String {
+        format!(
+            "{}{}/{}/{}/{}",
+            self.base_url, SECTION_GET_PATH, term_code, subject_code, course_id
+        )
+    }
+
+    /// Searches for courses matching `search`; see [`get_search_info`].
+    pub async fn search(
+        &self,
+        term_code: &str,
+        search: &str,
+        size: usize,
+        filters: CourseStatusFilters,
+    ) -> Result<SearchApiPing, MadpingerError> {
+        get_search_info(self, term_code, search, size, filters).await
+    }
+
+    /// Runs a search built with [`SearchQuery`]; see [`run_query`].
+    pub async fn run_query(&self, query: &SearchQuery) -> Result<SearchApiPing, MadpingerError> {
+        run_query(self, query).await
+    }
+
+    /// Runs a search across every page of results; see [`search_all`].
+    pub fn search_all(
+        &self,
+        query: SearchQuery,
+        limit: Option<usize>,
+    ) -> impl Stream<Item = Result<SearchedCourse, MadpingerError>> + '_ {
+        search_all(self, query, limit)
+    }
+
+    /// Keeps the courses with a package passing `query` and `filter`; see
+    /// [`retain_with_packages`].
+    pub async fn retain_with_packages(
+        &self,
+        courses: Vec<SearchedCourse>,
+        query: &SearchQuery,
+        filter: &PackageFilter,
+        concurrency: usize,
+    ) -> Vec<SearchedCourse> {
+        retain_with_packages(self, courses, query, filter, concurrency).await
+    }
+
+    /// Resolves subject names to codes; see [`resolve_subjects`].
+    pub async fn resolve_subjects(
+        &self,
+        term_code: &str,
+        subjects: Vec<SubjectFilter>,
+    ) -> Result<Vec<SubjectFilter>, MadpingerError> {
+        resolve_subjects(self, term_code, subjects).await
+    }
+
+    /// Retrieves the sections of a course; see [`get_section_info`].
+    pub async fn sections(
+        &self,
+        term_code: &str,
+        subject_code: &str,
+        course_id: &str,
+    ) -> Result<Vec<CourseSection>, MadpingerError> {
+        get_section_info(self, term_code, subject_code, course_id).await
+    }
+
+    /// Retrieves the sections of many courses concurrently; see [`get_sections_bulk`].
+    pub fn sections_bulk<'a, I>(
+        &'a self,
+        keys: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = (CourseKey, Result<Vec<CourseSection>, MadpingerError>)> + 'a
+    where
+        I: IntoIterator,
+        I::Item: Into<CourseKey> + 'a,
+        I::IntoIter: 'a,
+    {
+        get_sections_bulk(self, keys, concurrency)
+    }
+
+    /// Sends a request and returns the raw response body,
+    /// retrying transient failures according to the client's [`RetryPolicy`].
+    ///
+    /// Every attempt waits on the client's [`RateLimiter`], if it has one.
+    /// When replaying a [`Cassette`], nothing is sent; when recording one, the body is saved
+    /// (whether it came from the network or the cache).
+    /// Otherwise, fresh responses in the client's [`ResponseCache`] are used instead of the network.
+    ///
+    /// The body is passed to every [`ResponseObserver`] before it's returned.
+    pub async fn fetch(&self, request: &ApiRequest) -> Result<String, MadpingerError> {
+        let body = match &self.cassette {
+            Some(cassette) if cassette.mode() == CassetteMode::Replay => cassette.load(request)?,
+            cassette => {
+                let body = match self.cache.as_ref().and_then(|cache| cache.get(request)) {
+                    Some(body) => body,
+                    None => {
+                        let body = self.fetch_network(request).await?;
+                        if let Some(cache) = &self.cache {
+                            cache.put(request, &body);
+                        }
+                        body
+                    }
+                };
+                // cached bodies are recorded too, or the cassette couldn't replay them
+                if let Some(cassette) = cassette {
+                    cassette.store(request, &body)?;
+                }
+                body
+            }
+        };
+
+        for observer in &self.observers.0 {
+            observer.on_response(request, &body);
+        }
+        Ok(body)
+    }
+
+    /// Sends a request over the network, retrying it as needed.
+    async fn fetch_network(&self, request: &ApiRequest) -> Result<String, MadpingerError> {
+        let mut attempt = 1;
+        loop {
+            let err = match self.send(request).await {
+                Ok(body) => return Ok(body),
+                Err(err) => err,
+            };
+
+            let delay = match self.retry_policy.delay_for(&err, attempt) {
+                Some(delay) if attempt < self.retry_policy.max_attempts => delay,
+                _ => return Err(err),
+            };
+            log::warn!(
+                "attempt {}/{} failed ({}); retrying in {:.1}s",
+                attempt,
+                self.retry_policy.max_attempts,
+                err,
+                delay.as_secs_f64()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request once.
+    async fn send(&self, request: &ApiRequest) -> Result<String, MadpingerError> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+
+        let transport = |source| MadpingerError::Transport {
+            url: request.url.clone(),
+            source,
+        };
+
+        let mut hdrs = HeaderMap::new();
+        hdrs.insert(ACCEPT, HeaderValue::from_static("application/json")); // not required
+        hdrs.insert(
+            ACCEPT_ENCODING,
+            HeaderValue::from_static("gzip, deflate, br"),
+        ); // not required
+        hdrs.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.5")); // not required
+        hdrs.insert(CONNECTION, HeaderValue::from_static("keep-alive"));
+
+        let mut builder = self.http.request(request.method.clone(), &request.url);
+        if let Some(payload) = &request.payload {
+            hdrs.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            builder = builder.body(payload.clone());
+        }
+
+        let resp = builder.headers(hdrs).send().await.map_err(transport)?;
+        let status = resp.status();
+        let retry_after = retry_after(resp.headers());
+        let body = resp.text().await.map_err(transport)?;
+
+        if !status.is_success() {
+            return Err(MadpingerError::Status {
+                url: request.url.clone(),
+                status,
+                body: truncate(&body),
+                retry_after,
+            });
+        }
+        Ok(body)
+    }
+}
+
+/// Configures a [`CseClient`].
+#[derive(Debug)]
+pub struct CseClientBuilder {
+    base_url: String,
+    headers: HeaderMap,
+    connect_timeout: Duration,
+    timeout: Duration,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cassette: Option<Cassette>,
+    cache: Option<Arc<ResponseCache>>,
+    observers: Observers,
+}
+
+impl Default for CseClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            headers: default_client_headers(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: DEFAULT_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            cassette: None,
+            cache: None,
+            observers: Observers::default(),
+        }
+    }
+}
+
+impl CseClientBuilder {
+    /// Sets the base URL (scheme, host and optional path prefix) that endpoints are resolved against.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        let base_url: String = base_url.into();
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Adds (or replaces) a header sent with every request.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Replaces all headers sent with every request, including the defaults.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how failed requests are retried; use [`RetryPolicy::none`] to never retry.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limits the client (and its clones) to `requests_per_second`, without bursts.
+    pub fn rate_limit(self, requests_per_second: f64) -> Self {
+        self.rate_limiter(Arc::new(RateLimiter::new(requests_per_second, 1)))
+    }
+
+    /// Makes the client wait on a (possibly shared) rate limiter before every request.
+    pub fn rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Records responses to (or replays them from) a cassette.
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Caches responses on disk.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Adds an observer that's handed the raw body of every response.
+    pub fn observer(mut self, observer: impl ResponseObserver + 'static) -> Self {
+        self.observers.0.push(Arc::new(observer));
+        self
+    }
+
+    pub fn build(self) -> Result<CseClient, ReqwestError> {
+        let http = Client::builder()
+            .default_headers(self.headers)
+            .cookie_store(true)
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.timeout)
+            .build()?;
+
+        Ok(CseClient {
+            http,
+            base_url: self.base_url,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+            cassette: self.cassette,
+            cache: self.cache,
+            observers: self.observers,
+        })
+    }
+}
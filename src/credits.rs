@@ -0,0 +1,102 @@
+//! Credit ranges of courses and packages, and filtering on them.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::ParseFilterError;
+
+/// How many credits a course (or enrollment package) can be taken for, e.g. `1-3`.
+///
+/// Parses from the API's `creditRange` strings: a single number (`"3"`) or a range (`"1-3"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CreditRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl CreditRange {
+    pub fn new(min: usize, max: usize) -> Self {
+        Self {
+            min: min.min(max),
+            max: max.max(min),
+        }
+    }
+
+    /// A range holding a single number of credits.
+    pub fn exactly(credits: usize) -> Self {
+        Self::new(credits, credits)
+    }
+
+    pub fn is_variable(&self) -> bool {
+        self.min != self.max
+    }
+
+    pub fn contains(&self, credits: usize) -> bool {
+        self.min <= credits && credits <= self.max
+    }
+}
+
+impl FromStr for CreditRange {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|_| ParseFilterError::new("credit range", s))
+        };
+
+        match s.split_once('-') {
+            Some((min, max)) => Ok(Self::new(parse(min)?, parse(max)?)),
+            None => Ok(Self::exactly(parse(s)?)),
+        }
+    }
+}
+
+impl Display for CreditRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_variable() {
+            write!(f, "{}-{}", self.min, self.max)
+        } else {
+            write!(f, "{}", self.min)
+        }
+    }
+}
+
+/// Bounds on the number of credits a course is taken for.
+///
+/// A [`CreditRange`] passes if it can be taken for some number of credits within every bound;
+/// e.g. a `1-6` credit course passes `max: Some(3)`, since it can be taken for 3 credits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreditFilter {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    pub exact: Option<usize>,
+}
+
+impl CreditFilter {
+    pub fn new(min: Option<usize>, max: Option<usize>, exact: Option<usize>) -> Self {
+        Self { min, max, exact }
+    }
+
+    /// Whether there are any bounds at all.
+    pub fn is_set(&self) -> bool {
+        self.min.is_some() || self.max.is_some() || self.exact.is_some()
+    }
+
+    /// The lowest number of credits a course has to be available for.
+    pub(crate) fn lower(&self) -> Option<usize> {
+        self.min.max(self.exact)
+    }
+
+    /// The highest number of credits a course has to be available for.
+    pub(crate) fn upper(&self) -> Option<usize> {
+        [self.max, self.exact].into_iter().flatten().min()
+    }
+
+    pub fn matches(&self, credits: &CreditRange) -> bool {
+        let lo = self.lower().unwrap_or(0).max(credits.min);
+        let hi = self.upper().unwrap_or(usize::MAX).min(credits.max);
+        lo <= hi
+    }
+}
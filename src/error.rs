@@ -0,0 +1,125 @@
+//! Errors that can occur while talking to the CS&E API.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use thiserror::Error;
+
+/// How many characters of an offending value (or response body) are kept in an error.
+const SNIPPET_LEN: usize = 160;
+
+#[derive(Debug, Error)]
+pub enum MadpingerError {
+    /// The API answered, but with a non-success status code.
+    #[error("{url} responded with {status}")]
+    Status {
+        url: String,
+        status: StatusCode,
+        /// The (truncated) response body.
+        body: String,
+        /// How long the API asked us to wait before trying again, if it did.
+        retry_after: Option<Duration>,
+    },
+    /// The request couldn't be sent, or its response couldn't be read.
+    #[error("request to {url} failed: {source}")]
+    Transport {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A cassette is being replayed, but it has no response for the request.
+    #[error("no recorded response for {method} {url} (expected at {})", path.display())]
+    CassetteMiss {
+        method: String,
+        url: String,
+        path: PathBuf,
+    },
+    /// A subject was given by a name that none of the term's subjects go by.
+    #[error("no subject in term {term_code} is called {name:?}")]
+    UnknownSubject { name: String, term_code: String },
+    /// The response body doesn't match the schema.
+    #[error("couldn't deserialize the response from {url} at `{path}`: {message} (offending value: {snippet})")]
+    Json {
+        url: String,
+        /// Where in the body deserialization failed, e.g. `[3].sections[0].instructionMode`.
+        path: String,
+        message: String,
+        /// The (truncated) JSON found at `path`.
+        snippet: String,
+    },
+}
+
+/// A filter value (e.g. a `--level`) that doesn't name anything we know of.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unknown {kind} {value:?}")]
+pub struct ParseFilterError {
+    /// What was being parsed, e.g. `"level"`.
+    pub kind: &'static str,
+    pub value: String,
+}
+
+impl ParseFilterError {
+    pub(crate) fn new(kind: &'static str, value: &str) -> Self {
+        Self {
+            kind,
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Deserializes a response body, keeping track of where in the body things went wrong.
+pub(crate) fn decode<T: DeserializeOwned>(url: &str, body: &str) -> Result<T, MadpingerError> {
+    let value: Value = serde_json::from_str(body).map_err(|e| MadpingerError::Json {
+        url: url.to_string(),
+        path: ".".to_string(),
+        message: e.to_string(),
+        snippet: excerpt(body, e.line(), e.column()),
+    })?;
+
+    serde_path_to_error::deserialize(&value).map_err(|e| {
+        let snippet = lookup(&value, e.path())
+            .map(|v| truncate(&v.to_string()))
+            .unwrap_or_default();
+        MadpingerError::Json {
+            url: url.to_string(),
+            path: e.path().to_string(),
+            message: e.inner().to_string(),
+            snippet,
+        }
+    })
+}
+
+/// Finds the value that a deserialization path points to.
+fn lookup<'a>(value: &'a Value, path: &serde_path_to_error::Path) -> Option<&'a Value> {
+    let mut cur = value;
+    for segment in path.iter() {
+        cur = match segment {
+            Segment::Seq { index } => cur.get(index)?,
+            Segment::Map { key } => cur.get(key)?,
+            Segment::Enum { .. } => cur,
+            Segment::Unknown => return Some(cur),
+        };
+    }
+    Some(cur)
+}
+
+/// Cuts the text surrounding a (1-based) line and column out of a body.
+fn excerpt(body: &str, line: usize, column: usize) -> String {
+    let line = body.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+    let start = column.saturating_sub(SNIPPET_LEN / 2);
+    truncate(&line.chars().skip(start).collect::<String>())
+}
+
+pub(crate) fn truncate(s: &str) -> String {
+    if s.chars().count() <= SNIPPET_LEN {
+        s.to_string()
+    } else {
+        format!("{}...", s.chars().take(SNIPPET_LEN).collect::<String>())
+    }
+}
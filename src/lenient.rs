@@ -0,0 +1,52 @@
+//! Deserializing fields whose shape the API isn't consistent about.
+//!
+//! A field read with these helpers never fails the whole response: if its value doesn't have the
+//! expected shape, a warning is logged and the field falls back to `None` (or an empty list).
+//! Use them with `#[serde(default, deserialize_with = "...")]`, so a missing field is fine too.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// Reads an optional value; `null` and mismatched values become `None`.
+pub(crate) fn option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(parse(value))
+}
+
+/// Reads a list; `null` becomes an empty list, a lone value a list of one, and elements that
+/// don't match are skipped.
+pub(crate) fn vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let values = match Value::deserialize(deserializer)? {
+        Value::Array(values) => values,
+        Value::Null => vec![],
+        value => vec![value],
+    };
+    Ok(values.into_iter().filter_map(parse).collect())
+}
+
+fn parse<T: DeserializeOwned>(value: Value) -> Option<T> {
+    if value.is_null() {
+        return None;
+    }
+    match serde_json::from_value(value.clone()) {
+        Ok(parsed) => Some(parsed),
+        Err(e) => {
+            log::warn!(
+                "ignoring unexpected {}: {} ({})",
+                std::any::type_name::<T>(),
+                crate::error::truncate(&value.to_string()),
+                e
+            );
+            None
+        }
+    }
+}
@@ -1,21 +1,37 @@
 //! A program that polls the UW-Madison course search and enroll site (https://public.enroll.wisc.edu)
 
-use std::cmp::max;
-use std::fmt::{Display, Formatter};
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use section::breakdown::Bottleneck;
+use section::schema::{CourseSection, EnrollmentStatus, PackageEnrollmentStatus, Section, Status};
+use summary::summarize_packages;
 
-use reqwest::header::{HeaderMap, HeaderValue, HOST, USER_AGENT};
-use section::schema::{CourseSection, EnrollmentStatus, PackageEnrollmentStatus};
+pub use client::CseClient;
+pub use error::MadpingerError;
+pub use rate_limit::RateLimiter;
+pub use retry::RetryPolicy;
 
 pub const API_SRC_FILE: &str = "course_sections.csv";
 pub const DEFAULT_PAGE_SIZE: usize = 10;
 pub const DEFAULT_TERM_CODE: &str = "1242"; // fall '23
 pub const DEFAULT_LISTING_SIZE: usize = 5;
 
+pub mod cache;
+pub mod cassette;
+pub mod catalog;
+pub mod client;
+pub mod credits;
+pub mod error;
+mod lenient;
+pub mod rate_limit;
+pub mod retry;
 pub mod search;
 pub mod section;
+pub mod summary;
+pub mod timestamp;
 
 /// A set of filters representing a search filter
 /// for any combination of open, waitlisted, and closed courses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CourseStatusFilters {
     pub open: bool,
     pub waitlisted: bool,
@@ -30,6 +46,17 @@ impl CourseStatusFilters {
             closed,
         }
     }
+
+    /// Whether a package with this status is wanted; if none are set, any status goes.
+    pub fn matches(&self, status: &Status) -> bool {
+        match status {
+            _ if !(self.open || self.waitlisted || self.closed) => true,
+            Status::Open => self.open,
+            Status::Waitlisted => self.waitlisted,
+            Status::Closed => self.closed,
+            Status::Other(_) => false,
+        }
+    }
 }
 
 impl Default for CourseStatusFilters {
@@ -43,9 +70,10 @@ impl Default for CourseStatusFilters {
 }
 
 /// Returns default client headers for pinging the CS&E API.
+///
+/// The `Host` header is left out on purpose; it's derived from the URL of each request.
 pub fn default_client_headers() -> HeaderMap {
     let mut default_headers = HeaderMap::new();
-    default_headers.insert(HOST, HeaderValue::from_static("public.enroll.wisc.edu"));
     default_headers.insert(
         USER_AGENT,
         HeaderValue::from_static(
@@ -55,98 +83,107 @@ pub fn default_client_headers() -> HeaderMap {
     default_headers
 }
 
-/// Prints out sections of a course.
-pub fn report_course_sections(course_sections: &Vec<CourseSection>) {
+/// Prints out sections of a course; see [`summarize_packages`] for the numbers themselves.
+///
+/// Packages cross-listed under several subjects are printed once, with every designation and
+/// their combined enrollment. Each package is followed by its meetings and the days it runs, in
+/// campus time.
+pub fn report_course_sections(course_sections: &[CourseSection]) {
+    if course_sections.is_empty() {
+        eprintln!("No sections found.");
+    }
+
+    for summary in summarize_packages(course_sections) {
+        let topic_str = summary
+            .topic
+            .as_ref()
+            .map(|topic| format!(" \"{}\"", topic))
+            .unwrap_or_default();
+
+        match &summary.enrollment {
+            Some(enrollment) => println!(
+                "{} - {}{} ({}): {} ({} open seats, {}/{} enrolled, {}/{} waitlisted) [{}]",
+                summary.designation(),
+                summary.components.join(", "),
+                topic_str,
+                summary.mode,
+                summary.status.pad(),
+                enrollment.open_seats(),
+                enrollment.enrolled,
+                enrollment.capacity,
+                enrollment.waitlisted,
+                enrollment.waitlist_capacity,
+                enrollment.source
+            ),
+            None => println!(
+                "{} - (no sections){} ({}): {} (n/a)",
+                summary.designation(),
+                topic_str,
+                summary.mode,
+                summary.status.pad()
+            ),
+        }
+
+        // then when (and where) it meets
+        for meeting in &summary.meetings {
+            println!("    {}", meeting);
+        }
+        if let (Some(first_day), Some(last_day)) = (summary.first_day, summary.last_day) {
+            println!(
+                "    runs {} to {}",
+                first_day.format("%b %-d, %Y"),
+                last_day.format("%b %-d, %Y")
+            );
+        }
+    }
+}
+
+/// Prints out every component of each package with its own enrollment, and which of them is
+/// the bottleneck (e.g. why a package is closed while its lecture has open seats).
+pub fn report_package_breakdown(course_sections: &[CourseSection]) {
     if course_sections.is_empty() {
         eprintln!("No sections found.");
     }
 
     for cs in course_sections {
-        let CourseSection {
-            sections,
-            catalog_number,
-            ..
-        } = &cs;
         let PackageEnrollmentStatus { status, .. } = &cs.package_enrollment_status;
-
-        let course_code = format!(
-            "{} {}",
-            &sections
-                .get(0)
-                .expect("No sections were found!")
-                .subject
-                .short_description,
-            &catalog_number
+        println!(
+            "{} - {}: {}",
+            cs.designation().unwrap_or_default(),
+            components(&cs.sections),
+            status
         );
 
-        if let Some(EnrollmentStatus {
-            currently_enrolled: fb_enrolled, // fallback
-            capacity: fb_cap,                // fallback
-            waitlist_capacity: fb_wcap,      // fallback
-            waitlist_current_size: fb_wsize, // fallback
-            aggregate_currently_enrolled: agg_enrolled,
-            aggregate_capacity: agg_cap,
-            aggregate_waitlist_capacity: agg_wcap,
-            aggregate_waitlist_current_size: agg_wsize,
-            ..
-        }) = &cs.enrollment_status
-        {
-            let info_type = match (agg_cap, agg_enrolled, agg_wcap, agg_wsize) {
-                (Some(_), Some(_), Some(_), Some(_)) => EnrollInfoType::Aggregate,
-                (None, None, None, None) => EnrollInfoType::Fallback,
-                _ => EnrollInfoType::Mix,
-            };
-
-            // ..some course formatting
-            let mut meet_detail_str = String::new();
-            for (i, sec) in sections.iter().enumerate() {
-                meet_detail_str
-                    .push_str(format!("{} {}", sec.assembly_type, sec.section_number).as_str());
-                if i != sections.len() - 1 {
-                    // if not the last element, separate with comma
-                    meet_detail_str.push_str(", ");
-                }
-            }
-
-            // println!("data: {:?}", &cs);
-            // println!("sections {:?}", &sections);
-
-            let open_seats = match (agg_cap, agg_enrolled) {
-                (Some(cap), Some(enrolled)) => max(0, *cap as isize - *enrolled as isize) as usize,
-                _ => max(0, *fb_cap as isize - *fb_enrolled as isize) as usize,
-            };
-
-            // now print out the course detail
+        for section in &cs.sections {
+            let EnrollmentStatus {
+                capacity,
+                currently_enrolled,
+                waitlist_capacity,
+                waitlist_current_size,
+                open_seats,
+                ..
+            } = &section.enrollment_status;
             println!(
-                "{} - {}: {} ({} open seats, {}/{} enrolled, {}/{} waitlisted) [{}]",
-                course_code,
-                meet_detail_str,
-                status.pad(),
+                "    {}: {} open seats, {}/{} enrolled, {}/{} waitlisted",
+                section.component(),
                 open_seats,
-                agg_enrolled.map_or(fb_enrolled.to_string(), |v| v.to_string()),
-                agg_cap.map_or(fb_cap.to_string(), |v| v.to_string()),
-                agg_wsize.map_or(fb_wsize.to_string(), |v| v.to_string()),
-                agg_wcap.map_or(fb_wcap.to_string(), |v| v.to_string()),
-                info_type
+                currently_enrolled,
+                capacity,
+                waitlist_current_size,
+                waitlist_capacity
             );
-        } else {
-            println!("{} - (no sections): {} (n/a)", course_code, status.pad());
+        }
+        if let Some(bottleneck) = Bottleneck::of(cs) {
+            println!("    bottleneck: {}", bottleneck);
         }
     }
 }
 
-enum EnrollInfoType {
-    Aggregate,
-    Fallback,
-    Mix,
-}
-
-impl Display for EnrollInfoType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            EnrollInfoType::Aggregate => write!(f, "AGG"),
-            EnrollInfoType::Fallback => write!(f, "FB"),
-            EnrollInfoType::Mix => write!(f, "MIX"),
-        }
-    }
+/// The components of a package, e.g. `LEC 001, DIS 312`.
+fn components(sections: &[Section]) -> String {
+    sections
+        .iter()
+        .map(Section::component)
+        .collect::<Vec<_>>()
+        .join(", ")
 }
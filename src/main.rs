@@ -1,32 +1,216 @@
 use std::error::Error;
 use std::fs::File;
 use std::io::Write;
-use std::time::Duration;
+use std::path::Path;
+use std::sync::Mutex;
 
 use clap::Parser;
-use reqwest::header::{HeaderMap, HeaderValue, HOST, USER_AGENT};
-use reqwest::Client;
 
+use futures::TryStreamExt;
+use madpinger::catalog::{self, Catalog};
+use madpinger::client::{ApiRequest, ResponseObserver};
+use madpinger::search::merge_cross_listed;
+use madpinger::search::query::SearchQuery;
 use madpinger::search::schema::SearchedCourse;
-use madpinger::section::{get_section_info, SECTION_GET_URI_BASE};
+use madpinger::section::filter::PackageFilter;
+use madpinger::section::DEFAULT_BULK_CONCURRENCY;
 use madpinger::{
-    report_course_sections, search, CourseStatusFilters, DEFAULT_PAGE_SIZE,
+    report_course_sections, report_package_breakdown, CourseStatusFilters, DEFAULT_PAGE_SIZE,
     DEFAULT_TERM_CODE,
 };
-use search::get_search_info;
 
-use crate::config::{Action, Args};
-
-mod section;
+use crate::config::{catalog_path, Action, Args};
 
 mod config {
-    use clap::{command, Parser, Subcommand};
+    use std::error::Error;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use clap::{Parser, Subcommand};
+
+    use crate::RawDump;
+
+    use chrono::NaiveTime;
+    use madpinger::cache::ResponseCache;
+    use madpinger::cassette::Cassette;
+    use madpinger::client::DEFAULT_BASE_URL;
+    use madpinger::credits::CreditFilter;
+    use madpinger::search::offered::Season;
+    use madpinger::search::query::{Breadth, Level, SortOrder, SubjectFilter};
+    use madpinger::section::filter::{parse_time_of_day, Days, ModeFilter, ScheduleFilter};
+    use madpinger::{CseClient, RetryPolicy};
 
     #[derive(Parser, Debug)]
     #[command(author, version, about, long_about = None)]
     pub struct Args {
         #[clap(subcommand)]
         pub(crate) action: Action,
+
+        /// Base URL of the CS&E site (or a stand-in/proxy for it)
+        #[clap(long, global = true, default_value = DEFAULT_BASE_URL)]
+        pub(crate) base_url: String,
+
+        /// How many times to retry a request that failed for a transient reason
+        #[clap(long, global = true, default_value_t = 3)]
+        pub(crate) retries: u32,
+
+        /// Base delay between retries in milliseconds (doubles after each retry)
+        #[clap(long, global = true, default_value_t = 500)]
+        pub(crate) retry_delay: u64,
+
+        /// Send at most this many requests per second
+        #[clap(long, global = true)]
+        pub(crate) rps: Option<f64>,
+
+        /// Save every raw response body into this directory
+        #[clap(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+        pub(crate) record: Option<PathBuf>,
+
+        /// Serve responses from a directory made with `--record` instead of the network
+        #[clap(long, global = true, value_name = "DIR")]
+        pub(crate) replay: Option<PathBuf>,
+
+        /// Write the raw body of every response to this file, one per line
+        #[clap(long, global = true, value_name = "PATH")]
+        pub(crate) dump_raw: Option<PathBuf>,
+
+        /// Don't read or write the response cache
+        #[clap(long, global = true, conflicts_with = "refresh")]
+        pub(crate) no_cache: bool,
+
+        /// Ignore cached responses, but cache the fresh ones
+        #[clap(long, global = true)]
+        pub(crate) refresh: bool,
+
+        /// Where to cache responses; defaults to the user's cache directory
+        #[clap(long, global = true, value_name = "DIR")]
+        pub(crate) cache_dir: Option<PathBuf>,
+
+        /// Print how the cache was used when done
+        #[clap(long, global = true)]
+        pub(crate) cache_stats: bool,
+    }
+
+    impl Args {
+        /// Builds a client configured by the global flags.
+        pub fn client(&self) -> Result<CseClient, Box<dyn Error>> {
+            let mut builder =
+                CseClient::builder()
+                    .base_url(&self.base_url)
+                    .retry_policy(RetryPolicy::new(
+                        self.retries + 1,
+                        Duration::from_millis(self.retry_delay),
+                    ));
+            if let Some(rps) = self.rps.filter(|rps| *rps > 0.0) {
+                builder = builder.rate_limit(rps);
+            }
+            if let Some(dir) = &self.record {
+                builder = builder.cassette(Cassette::record(dir));
+            }
+            if let Some(dir) = &self.replay {
+                builder = builder.cassette(Cassette::replay(dir));
+            }
+            if let (false, Some(dir)) = (self.no_cache, self.cache_dir()) {
+                builder = builder.cache(ResponseCache::new(dir).refresh(self.refresh));
+            }
+            if let Some(path) = &self.dump_raw {
+                builder = builder.observer(RawDump::create(path)?);
+            }
+            Ok(builder.build()?)
+        }
+
+        /// Where cached responses and downloaded catalogs are kept.
+        pub fn cache_dir(&self) -> Option<PathBuf> {
+            self.cache_dir
+                .clone()
+                .or_else(|| dirs::cache_dir().map(|d| d.join("madpinger")))
+        }
+    }
+
+    /// Where the catalog of a term is saved in the cache directory, unless another path is given.
+    pub fn catalog_path(
+        cache_dir: Option<&Path>,
+        term_code: &str,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = cache_dir.ok_or("no cache directory; pass a catalog path")?;
+        Ok(dir.join(format!("catalog-{}.json", term_code)))
+    }
+
+    /// Bounds on credits, shared by the commands that filter on them.
+    #[derive(Debug, clap::Args, PartialEq, Eq)]
+    pub struct CreditArgs {
+        /// Only show courses that can be taken for at least this many credits
+        #[clap(long, value_name = "CREDITS")]
+        pub(crate) min_credits: Option<usize>,
+
+        /// Only show courses that can be taken for at most this many credits
+        #[clap(long, value_name = "CREDITS")]
+        pub(crate) max_credits: Option<usize>,
+
+        /// Only show courses that can be taken for exactly this many credits
+        #[clap(long, value_name = "CREDITS")]
+        pub(crate) exact_credits: Option<usize>,
+    }
+
+    impl CreditArgs {
+        pub fn filter(&self) -> CreditFilter {
+            CreditFilter::new(self.min_credits, self.max_credits, self.exact_credits)
+        }
+    }
+
+    /// How a course is taught, shared by the commands that filter on it; any of the given
+    /// modes will do.
+    #[derive(Debug, clap::Args, PartialEq, Eq)]
+    pub struct ModeArgs {
+        /// Only show online-only courses
+        #[clap(long)]
+        pub(crate) online: bool,
+
+        /// Only show asynchronous courses (no scheduled meetings)
+        #[clap(long = "async")]
+        pub(crate) asynchronous: bool,
+
+        /// Only show courses that meet in a classroom
+        #[clap(long)]
+        pub(crate) in_person: bool,
+    }
+
+    impl ModeArgs {
+        pub fn filter(&self) -> ModeFilter {
+            ModeFilter::new(self.online, self.asynchronous, self.in_person)
+        }
+    }
+
+    /// When a course meets, shared by the commands that filter on it; every class meeting of a
+    /// package has to fit.
+    #[derive(Debug, clap::Args, PartialEq, Eq)]
+    pub struct ScheduleArgs {
+        /// Only show packages meeting on these days alone, e.g. TR or "tue,thu"
+        #[clap(long, value_name = "DAYS")]
+        pub(crate) days: Option<Days>,
+
+        /// Only show packages not meeting on any of these days, e.g. F
+        #[clap(long, value_name = "DAYS")]
+        pub(crate) not_days: Option<Days>,
+
+        /// Only show packages whose meetings start at or after this time, e.g. 11:00 or 1pm
+        #[clap(long, value_name = "TIME", value_parser = parse_time_of_day)]
+        pub(crate) after: Option<NaiveTime>,
+
+        /// Only show packages whose meetings end at or before this time, e.g. 17:00
+        #[clap(long, value_name = "TIME", value_parser = parse_time_of_day)]
+        pub(crate) before: Option<NaiveTime>,
+    }
+
+    impl ScheduleArgs {
+        pub fn filter(&self) -> ScheduleFilter {
+            ScheduleFilter {
+                days: self.days,
+                not_days: self.not_days.unwrap_or_default(),
+                after: self.after,
+                before: self.before,
+            }
+        }
     }
 
     #[derive(Debug, Subcommand, PartialEq, Eq)]
@@ -40,6 +224,19 @@ mod config {
 
             #[clap(short, long)]
             term_code: Option<String>,
+
+            #[clap(flatten)]
+            credits: CreditArgs,
+
+            #[clap(flatten)]
+            modes: ModeArgs,
+
+            #[clap(flatten)]
+            schedule: ScheduleArgs,
+
+            /// List the enrollment of every component of each package, and which one is full
+            #[clap(long)]
+            breakdown: bool,
         },
         Search {
             #[clap(value_parser)]
@@ -59,6 +256,83 @@ mod config {
 
             #[clap(short, long)]
             closed: bool,
+
+            /// Fetch every page of results (each of size `-s`)
+            #[clap(long, conflicts_with = "page")]
+            all: bool,
+
+            /// Which page of results to show, starting at 1
+            #[clap(long)]
+            page: Option<usize>,
+
+            /// Only show courses in this subject, given by code (266) or name ("COMP SCI");
+            /// may be repeated
+            #[clap(long = "subject", value_name = "CODE|NAME")]
+            subjects: Vec<SubjectFilter>,
+
+            /// Only show courses counting towards this breadth, by code (N) or name
+            /// ("natural science"); may be repeated
+            #[clap(long = "breadth", value_name = "BREADTH")]
+            breadths: Vec<Breadth>,
+
+            /// Only show courses meeting this general education requirement (COM-B, QR-A, ...);
+            /// may be repeated
+            #[clap(long = "gen-ed", value_name = "GEN_ED")]
+            general_ed: Vec<String>,
+
+            /// Only show courses meeting the ethnic studies requirement
+            #[clap(long)]
+            ethnic_studies: bool,
+
+            /// Only show courses at this level (Elementary, Intermediate, Advanced); may be repeated
+            #[clap(long = "level", value_name = "LEVEL")]
+            levels: Vec<Level>,
+
+            #[clap(flatten)]
+            credits: CreditArgs,
+
+            #[clap(flatten)]
+            modes: ModeArgs,
+
+            #[clap(flatten)]
+            schedule: ScheduleArgs,
+
+            /// Only show courses typically offered in this season (fall, spring, summer); may be
+            /// repeated
+            #[clap(long = "offered", value_name = "SEASON")]
+            offered: Vec<Season>,
+
+            /// Only show special topics courses with a topic mentioning this text
+            #[clap(long, value_name = "TEXT")]
+            topic: Option<String>,
+
+            /// How to order results: relevance, subject, catalog-number, last-taught, credits or
+            /// title (the API can't sort by the last two, so without --all they only sort the
+            /// fetched page)
+            #[clap(long, value_name = "ORDER", default_value_t = SortOrder::Score)]
+            sort: SortOrder,
+
+            /// Search a catalog downloaded with `madpinger catalog` instead of the API
+            #[clap(long)]
+            offline: bool,
+
+            /// The catalog to search with `--offline`; defaults to the term's catalog in the cache directory
+            #[clap(long, value_name = "PATH", requires = "offline")]
+            catalog: Option<PathBuf>,
+        },
+        /// Download every course of a term, for `search --offline`
+        Catalog {
+            #[clap(short, long)]
+            term_code: Option<String>,
+
+            /// Also fetch every course's packages, to learn who teaches it and to filter on them
+            /// offline (much slower)
+            #[clap(long)]
+            with_packages: bool,
+
+            /// Where to save the catalog; defaults to the term's catalog in the cache directory
+            #[clap(short, long, value_name = "PATH")]
+            output: Option<PathBuf>,
         },
         Listing {
             #[clap(value_parser, short, long)]
@@ -73,45 +347,70 @@ mod config {
     }
 }
 
+/// Writes every raw response body to a file, one body per line.
+struct RawDump {
+    file: Mutex<File>,
+}
+
+impl RawDump {
+    fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            file: Mutex::new(File::create(path)?),
+        })
+    }
+}
+
+impl ResponseObserver for RawDump {
+    fn on_response(&self, request: &ApiRequest, body: &str) {
+        let mut file = self.file.lock().unwrap();
+        if let Err(e) = writeln!(file, "{}", body.trim_end()) {
+            log::warn!("couldn't dump the response from {}: {}", request.url, e);
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let Args { action, .. } = Args::parse();
-
-    let mut default_headers = HeaderMap::new();
-    default_headers.insert(HOST, HeaderValue::from_static("public.enroll.wisc.edu"));
-    default_headers.insert(
-        USER_AGENT,
-        HeaderValue::from_static(
-            "Mozilla/5.0 (X11; Ubuntu; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/111.0",
-        ),
-    );
-
-    let client = Client::builder()
-        .default_headers(default_headers)
-        .cookie_store(true)
-        .connect_timeout(Duration::from_secs(10))
-        .timeout(Duration::from_secs(10))
-        .build()?;
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = Args::parse();
+    let client = args.client()?;
+    let cache_dir = args.cache_dir();
+    let Args {
+        action,
+        cache_stats,
+        ..
+    } = args;
 
     if let Action::Section {
         subject_code, // e.g. "266"
         course_id,    // e.g. "024798"
         term_code,    // e.g. "1234" or "1424"
-        ..
+        credits,
+        modes,
+        schedule,
+        breakdown,
     } = action
     {
         let term_code = term_code.unwrap_or_else(|| DEFAULT_TERM_CODE.to_string()); // default spring '23 term code
 
-        let url = format!(
-            "{}/{}/{}/{}",
-            SECTION_GET_URI_BASE, &term_code, &subject_code, &course_id
-        );
+        let url = client.sections_url(&term_code, &subject_code, &course_id);
         println!("reading/deserializing json response at {url}..");
-        let course_sections =
-            get_section_info(&client, &term_code, &subject_code, &course_id).await?;
+        let course_sections = client
+            .sections(&term_code, &subject_code, &course_id)
+            .await?;
+        let course_sections = PackageFilter::new()
+            .credits(credits.filter())
+            .modes(modes.filter())
+            .schedule(schedule.filter())
+            .apply(course_sections);
 
         println!("listing important section information for course id {course_id}..");
-        report_course_sections(&course_sections);
+        if breakdown {
+            report_package_breakdown(&course_sections);
+        } else {
+            report_course_sections(&course_sections);
+        }
     } else if let Action::Search {
         search_key,
         size,
@@ -119,6 +418,21 @@ async fn main() -> Result<(), Box<dyn Error>> {
         open,
         waitlisted,
         closed,
+        all,
+        page,
+        subjects,
+        breadths,
+        general_ed,
+        ethnic_studies,
+        levels,
+        credits,
+        modes,
+        schedule,
+        offered,
+        topic,
+        sort,
+        offline,
+        catalog,
     } = action
     {
         // If no flags were passed and default to false, just invert to true; doesn't make sense to get no result
@@ -132,36 +446,153 @@ async fn main() -> Result<(), Box<dyn Error>> {
         let term_code = term_code.unwrap_or_else(|| DEFAULT_TERM_CODE.to_string()); // default spring '23 term code
         let size = size.unwrap_or(DEFAULT_PAGE_SIZE);
         println!("Searching for '{search_key}' in term {}...", &term_code);
-        let api_ping =
-            get_search_info(client, &term_code, &search_key, size, status_filters).await?;
+        // A catalog holds subject names too, so they're only resolved to codes for the API.
+        let subjects = if offline {
+            subjects
+        } else {
+            client.resolve_subjects(&term_code, subjects).await?
+        };
+        let query = SearchQuery::new(&term_code)
+            .query(&search_key)
+            .statuses(status_filters)
+            .page_size(size)
+            .page(page.unwrap_or(1))
+            .subjects(subjects)
+            .ethnic_studies(ethnic_studies)
+            .credits(credits.filter())
+            .modes(modes.filter())
+            .sort_order(sort);
+        let query = breadths.into_iter().fold(query, SearchQuery::breadth);
+        let query = general_ed.into_iter().fold(query, SearchQuery::general_ed);
+        let query = levels.into_iter().fold(query, SearchQuery::level);
+        let query = offered.into_iter().fold(query, SearchQuery::offered);
+        let query = topic.iter().fold(query, SearchQuery::topic);
+
+        let package_filter = PackageFilter::new().schedule(schedule.filter());
+        let (num_hits, hits): (usize, Vec<SearchedCourse>) = if offline {
+            let path = match catalog {
+                Some(path) => path,
+                None => catalog_path(cache_dir.as_deref(), &term_code)?,
+            };
+            let catalog = Catalog::load(&path)?;
+            if !catalog.has_packages()
+                && ((open, waitlisted, closed) != (true, true, true)
+                    || query.modes.is_set()
+                    || package_filter.is_set())
+            {
+                log::warn!(
+                    "the catalog has no packages (download it with --with-packages); status, mode and schedule filters are ignored"
+                );
+            }
+            let index = catalog.index();
+            if all {
+                let hits: Vec<SearchedCourse> = index
+                    .search(&query, &package_filter)
+                    .into_iter()
+                    .cloned()
+                    .collect();
+                (hits.len(), hits)
+            } else {
+                let page = index.search_page(&query, &package_filter);
+                (page.found, page.hits)
+            }
+        } else if all {
+            let hits: Vec<SearchedCourse> =
+                client.search_all(query.clone(), None).try_collect().await?;
+            (hits.len(), hits)
+        } else {
+            let api_ping = client.run_query(&query).await?;
+            (api_ping.found, api_ping.hits)
+        };
+
+        // Search results don't carry meeting times, so those are checked package by package.
+        let (num_hits, hits) = if package_filter.is_set() && !offline {
+            let hits = client
+                .retain_with_packages(hits, &query, &package_filter, DEFAULT_BULK_CONCURRENCY)
+                .await;
+            (hits.len(), hits)
+        } else {
+            (num_hits, hits)
+        };
 
-        let num_hits = &api_ping.found;
-        let hits = api_ping.hits;
+        // A cross-listed course is a hit under each of its subjects; it's shown just once.
+        let listings = hits.len();
+        let hits = merge_cross_listed(hits);
+        let num_hits = num_hits.saturating_sub(listings - hits.len());
 
         println!("found {} hits", num_hits);
         let mut f: File = File::create("out/search_results.csv")?;
-        f.write_all(b"term_code,subject_code,course_id,course_designation,title\n")?;
+        f.write_all(
+            b"term_code,subject_code,course_id,course_designation,title,grading_basis,cross_listed_as\n",
+        )?;
         for sc in &hits {
             let SearchedCourse {
                 course_designation,
                 course_id,
                 title,
                 subject,
+                grading_basis,
                 ..
             } = sc;
+            let grading_basis = grading_basis
+                .as_ref()
+                .map_or("", |basis| basis.description.as_str());
+            let cross_listed = sc.cross_listed_designations().join("; ");
 
             println!(
-                "cid: {:<10} sc: {:<3} - {:<15} - {}",
-                course_id, subject.subject_code, course_designation, title
+                "cid: {:<10} sc: {:<3} - {:<15} - {} ({} cr; typically {})",
+                course_id,
+                subject.subject_code,
+                course_designation,
+                title,
+                sc.credits(),
+                sc.offered()
             );
+            if !cross_listed.is_empty() {
+                println!("{:>24} also listed as {}", "", cross_listed);
+            }
+            for matching in topic.iter().flat_map(|t| sc.topics_matching(t)) {
+                println!("{:>24} topic: {}", "", matching.short_description);
+            }
             f.write_all(
                 format!(
-                    "{},{},{},\"{}\",\"{}\"\n",
-                    subject.term_code, subject.subject_code, course_id, course_designation, title
+                    "{},{},{},\"{}\",\"{}\",\"{}\",\"{}\"\n",
+                    subject.term_code,
+                    subject.subject_code,
+                    course_id,
+                    course_designation,
+                    title,
+                    grading_basis,
+                    cross_listed
                 )
                 .as_bytes(),
             )?;
         }
+    } else if let Action::Catalog {
+        term_code,
+        with_packages,
+        output,
+    } = action
+    {
+        let term_code = term_code.unwrap_or_else(|| DEFAULT_TERM_CODE.to_string());
+        let path = match output {
+            Some(path) => path,
+            None => catalog_path(cache_dir.as_deref(), &term_code)?,
+        };
+
+        println!("downloading every course in term {}..", &term_code);
+        let catalog =
+            catalog::download(&client, &term_code, with_packages, DEFAULT_BULK_CONCURRENCY).await?;
+        catalog.save(&path)?;
+        println!(
+            "saved {} courses to {}",
+            catalog.entries.len(),
+            path.display()
+        );
+    }
+
+    if let (true, Some(cache)) = (cache_stats, client.cache()) {
+        eprintln!("cache ({}): {}", cache.dir().display(), cache.stats());
     }
     Ok(())
 }
@@ -0,0 +1,76 @@
+//! Client-side rate limiting of API requests.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::{sleep, Instant};
+
+/// A token bucket that caps how many requests are sent per second.
+///
+/// A limiter is usually shared (through an `Arc`) by every clone of a
+/// [`CseClient`](crate::CseClient), so concurrent tasks draw from the same budget.
+/// Requests wait in the order they asked for a token.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// May go negative; that's how many tokens have been promised to waiting requests.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Allows `requests_per_second` on average, with bursts of up to `burst` requests.
+    ///
+    /// # Panics
+    /// Panics if `requests_per_second` isn't positive.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second > 0.0,
+            "requests_per_second must be positive"
+        );
+        let burst = burst.max(1) as f64;
+
+        Self {
+            requests_per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    /// Waits until a request may be sent.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let now = Instant::now();
+            let refill =
+                now.duration_since(bucket.refilled_at).as_secs_f64() * self.requests_per_second;
+            bucket.tokens = (bucket.tokens + refill).min(self.burst);
+            bucket.refilled_at = now;
+
+            bucket.tokens -= 1.0;
+            if bucket.tokens < 0.0 {
+                Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            log::debug!("rate limited; waiting {:.2}s", wait.as_secs_f64());
+            sleep(wait).await;
+        }
+    }
+}
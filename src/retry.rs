@@ -0,0 +1,111 @@
+//! Retrying requests that failed for transient reasons.
+
+use std::time::{Duration, SystemTime};
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use crate::error::MadpingerError;
+
+/// How (and how often) failed requests are retried.
+///
+/// Requests are retried when the API answers with `429`, `502`, `503` or `504`, or when the
+/// connection fails or times out. The delay before retry `n` is `base_delay * 2^(n - 1)`, capped
+/// at `max_delay`, unless the response carried a `Retry-After` header, which is honored instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Whether to randomize each backoff delay (to somewhere between half and all of it).
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            ..Default::default()
+        }
+    }
+
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    /// The delay before retry number `retry` (starting at 1), ignoring `Retry-After`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        if self.jitter && !delay.is_zero() {
+            rand::thread_rng().gen_range(delay / 2..=delay)
+        } else {
+            delay
+        }
+    }
+
+    /// The delay before retry number `retry` after `err`, or `None` if `err` shouldn't be retried.
+    pub(crate) fn delay_for(&self, err: &MadpingerError, retry: u32) -> Option<Duration> {
+        match err {
+            MadpingerError::Status {
+                status,
+                retry_after,
+                ..
+            } if is_transient(*status) => Some(
+                retry_after
+                    .map(|d| d.min(self.max_delay))
+                    .unwrap_or_else(|| self.backoff(retry)),
+            ),
+            MadpingerError::Transport { source, .. }
+                if source.is_timeout() || source.is_connect() =>
+            {
+                Some(self.backoff(retry))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Reads a `Retry-After` header, given either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
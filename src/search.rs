@@ -1,120 +1,261 @@
-//! Pertains to information about a specific section.
+//! Pertains to searching for courses.
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
-use std::fs::File;
-use std::io::{stdout, Read, Write};
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
 
-use reqwest::header::{
-    HeaderMap, HeaderValue, ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, CONNECTION, CONTENT_LENGTH,
-    CONTENT_TYPE, USER_AGENT,
-};
-use reqwest::Client;
-use reqwest::Error as ReqwestError;
-use serde::Serialize;
+use futures::future;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 
+use crate::client::{ApiRequest, CseClient, Endpoint};
+use crate::error::{decode, MadpingerError};
+use crate::search::query::{normalize_subject_name, SearchQuery, SubjectFilter};
 use crate::search::schema::{SearchApiPing, SearchedCourse};
+use crate::section::filter::PackageFilter;
+use crate::section::{get_sections_bulk, CourseKey};
 use crate::CourseStatusFilters;
 
-const SEARCH_POST_URI_BASE: &str = "https://public.enroll.wisc.edu/api/search/v1";
-const OUTPUT_FILE_NAME: &str = "response.json";
+/// Path of the search endpoint, relative to the client's base URL.
+pub const SEARCH_POST_PATH: &str = "/api/search/v1";
 
+pub mod offered;
+pub mod query;
+
+/// Builds the body of a search request.
 pub fn get_payload(
     term_code: &str,
     search: &str,
     page_size: usize,
     filters: CourseStatusFilters,
 ) -> String {
-    let open_str = if filters.open { "OPEN" } else { "" };
-    let waitlisted_str = if filters.waitlisted { "WAITLISTED" } else { "" };
-    let closed_str = if filters.closed { "CLOSED" } else { "" };
-
-    let s: String = format!(
-        r##"{{
-    "selectedTerm": "{term_code}",
-    "queryString": "{search}",
-    "filters": [
-    {{
-      "has_child": {{
-        "type": "enrollmentPackage",
-        "query": {{
-          "bool": {{
-            "must": [
-            {{
-              "match": {{
-                "packageEnrollmentStatus.status": "{open_str} {waitlisted_str} {closed_str}"
-              }}
-            }},
-            {{
-              "match": {{
-                "published": true
-              }}
-            }}
-            ]
-          }}
-        }}
-      }}
-    }}
-    ],
-    "page": 1,
-    "pageSize": {page_size},
-    "sortOrder": "SCORE"
-  }}"##
-    );
-
-    s
+    let query = SearchQuery::new(term_code)
+        .query(search)
+        .statuses(filters)
+        .page_size(page_size);
+
+    serde_json::to_string(&query).expect("couldn't convert to string")
 }
 
 pub async fn get_search_info(
-    client: Client,
+    client: &CseClient,
     term_code: &str,
     search: &str,
     size: usize,
     filters: CourseStatusFilters,
-) -> Result<SearchApiPing, ReqwestError> {
-    let payload = get_payload(term_code, search, size, filters);
-
-    // println!("Request body len: {}", payload.len());
-    let mut hdrs = HeaderMap::new();
-    hdrs.insert(ACCEPT, HeaderValue::from_static("application/json")); // not required
-    hdrs.insert(
-        ACCEPT_ENCODING,
-        HeaderValue::from_static("gzip, deflate, br"),
-    ); // not required
-    hdrs.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.5")); // not required
-    hdrs.insert(CONNECTION, HeaderValue::from_static("keep-alive"));
-    hdrs.insert(CONTENT_LENGTH, HeaderValue::from(payload.len()));
-    hdrs.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-    let resp = client
-        .post(SEARCH_POST_URI_BASE)
-        .headers(hdrs)
-        .body(payload)
-        .send()
+) -> Result<SearchApiPing, MadpingerError> {
+    let query = SearchQuery::new(term_code)
+        .query(search)
+        .statuses(filters)
+        .page_size(size);
+
+    run_query(client, &query).await
+}
+
+/// Runs a search; this returns a single page of results.
+///
+/// Hits that don't pass [`SearchQuery::matches`] are dropped, so a page may hold fewer than
+/// `page_size` of them; `found` is left as the API reported it. Sort orders the API doesn't
+/// support are applied to the hits of the page.
+pub async fn run_query(
+    client: &CseClient,
+    query: &SearchQuery,
+) -> Result<SearchApiPing, MadpingerError> {
+    let mut ping = fetch_page(client, query).await?;
+    ping.hits.retain(|course| query.matches(course));
+    if !query.sort_order.is_server_side() {
+        query.sort_order.sort(&mut ping.hits);
+    }
+    Ok(ping)
+}
+
+/// Fetches a page of results exactly as the API returned it.
+async fn fetch_page(
+    client: &CseClient,
+    query: &SearchQuery,
+) -> Result<SearchApiPing, MadpingerError> {
+    let payload = serde_json::to_string(query).expect("couldn't convert to string");
+
+    let url = client.search_url();
+    let body = client
+        .fetch(&ApiRequest::post(Endpoint::Search, url.clone(), payload))
         .await?;
 
-    println!("Response status: {:#?}", resp.status());
+    decode(&url, &body)
+}
+
+/// Runs a search across every page of results (starting at `query.page`),
+/// yielding courses one at a time.
+///
+/// Pages are fetched lazily, and the stream ends once the API runs out of hits (or all `found`
+/// courses were seen) or `limit` courses were yielded. Courses come in the order the API returns
+/// them; for a [`SortOrder`] the API doesn't support, every page is fetched and sorted before the
+/// first course is yielded.
+pub fn search_all(
+    client: &CseClient,
+    query: SearchQuery,
+    limit: Option<usize>,
+) -> impl Stream<Item = Result<SearchedCourse, MadpingerError>> + '_ {
+    let sort_order = query.sort_order;
+    let filter = query.clone();
+    let pages = stream::try_unfold(Some(query), move |query| async move {
+        let mut query = match query {
+            Some(query) => query,
+            None => return Ok::<_, MadpingerError>(None),
+        };
 
-    let searched_courses = resp.json::<SearchApiPing>().await?;
+        let SearchApiPing { found, hits } = fetch_page(client, &query).await?;
+        let last_page = hits.is_empty()
+            || hits.len() < query.page_size
+            || query.page * query.page_size >= found;
+        let next = if last_page {
+            None
+        } else {
+            query.page += 1;
+            Some(query)
+        };
+        Ok(Some((hits, next)))
+    });
+
+    let courses = pages
+        .map_ok(|hits| stream::iter(hits.into_iter().map(Ok)))
+        .try_flatten()
+        .try_filter(move |course| future::ready(filter.matches(course)));
+
+    let courses = if sort_order.is_server_side() {
+        courses.left_stream()
+    } else {
+        stream::once(courses.try_collect::<Vec<_>>())
+            .map_ok(move |mut courses| {
+                sort_order.sort(&mut courses);
+                stream::iter(courses.into_iter().map(Ok))
+            })
+            .try_flatten()
+            .right_stream()
+    };
+    courses.take(limit.unwrap_or(usize::MAX))
+}
+
+/// Keeps the courses with at least one enrollment package that passes `filter` and the
+/// statuses and modes of `query` (see [`SearchQuery::matches_package`]), in order.
+///
+/// Search results don't say when (or how) packages meet, so the packages of every course are
+/// fetched, at most `concurrency` courses at once. A course whose packages can't be fetched is
+/// dropped (with a warning) rather than failing the rest.
+pub async fn retain_with_packages(
+    client: &CseClient,
+    mut courses: Vec<SearchedCourse>,
+    query: &SearchQuery,
+    filter: &PackageFilter,
+    concurrency: usize,
+) -> Vec<SearchedCourse> {
+    let keys: Vec<CourseKey> = courses.iter().map(CourseKey::from).collect();
+
+    let mut passing = HashSet::new();
+    let mut results = get_sections_bulk(client, keys, concurrency);
+    while let Some((key, packages)) = results.next().await {
+        match packages {
+            Ok(packages)
+                if packages
+                    .iter()
+                    .any(|package| query.matches_package(package, filter)) =>
+            {
+                passing.insert(key);
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("skipping {}: couldn't fetch its packages ({})", key, e),
+        }
+    }
+
+    courses.retain(|course| passing.contains(&CourseKey::from(course)));
+    courses
+}
+
+/// Merges the hits that are listings of the same course under different subjects (they share a
+/// course ID), keeping the first; the subjects of the others are added to its
+/// `all_cross_listed_subjects`, so it shows every designation.
+pub fn merge_cross_listed(courses: Vec<SearchedCourse>) -> Vec<SearchedCourse> {
+    let mut merged: Vec<SearchedCourse> = Vec::with_capacity(courses.len());
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+    for course in courses {
+        let key = (course.term_code.clone(), course.course_id.clone());
+        match seen.get(&key) {
+            Some(&i) => {
+                let kept = &mut merged[i];
+                let subjects =
+                    std::iter::once(course.subject).chain(course.all_cross_listed_subjects);
+                for subject in subjects {
+                    let listed = kept
+                        .all_cross_listed_subjects
+                        .iter()
+                        .any(|s| s.subject_code == subject.subject_code);
+                    if !listed && subject.subject_code != kept.subject.subject_code {
+                        kept.all_cross_listed_subjects.push(subject);
+                    }
+                }
+            }
+            None => {
+                seen.insert(key, merged.len());
+                merged.push(course);
+            }
+        }
+    }
+    merged
+}
 
-    File::create(OUTPUT_FILE_NAME)
-        .unwrap()
-        .write_all(
-            serde_json::to_string(&searched_courses)
-                .expect("couldn't convert to string")
-                .as_bytes(),
-        )
-        .expect("failed to write");
+/// Turns every [`SubjectFilter::Name`] into the matching [`SubjectFilter::Code`], so the API
+/// can filter on it.
+///
+/// Each name is searched for, and its code is read off the subjects of the hits (their own, or
+/// one they're cross-listed under). A name that matches no hit's subject is an error, rather than
+/// a search that silently finds nothing.
+pub async fn resolve_subjects(
+    client: &CseClient,
+    term_code: &str,
+    subjects: Vec<SubjectFilter>,
+) -> Result<Vec<SubjectFilter>, MadpingerError> {
+    let mut resolved = Vec::with_capacity(subjects.len());
+    for subject in subjects {
+        let name = match subject {
+            SubjectFilter::Name(name) => name,
+            code => {
+                resolved.push(code);
+                continue;
+            }
+        };
 
-    Ok(searched_courses)
+        let wanted = normalize_subject_name(&name);
+        let ping = fetch_page(client, &SearchQuery::new(term_code).query(&name)).await?;
+        let code = ping
+            .hits
+            .iter()
+            .flat_map(|course| {
+                std::iter::once(&course.subject).chain(&course.all_cross_listed_subjects)
+            })
+            .find(|subject| normalize_subject_name(&subject.short_description) == wanted)
+            .map(|subject| subject.subject_code.clone());
+        match code {
+            Some(code) => resolved.push(SubjectFilter::Code(code)),
+            None => {
+                return Err(MadpingerError::UnknownSubject {
+                    name,
+                    term_code: term_code.to_string(),
+                })
+            }
+        }
+    }
+    Ok(resolved)
 }
 
 pub mod schema {
+    use std::collections::BTreeMap;
+
+    use crate::credits::CreditRange;
+    use crate::lenient;
+    use crate::search::offered::TypicallyOffered;
     use crate::section;
     use serde::{Deserialize, Serialize};
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct SearchApiPing {
         pub found: usize,
@@ -131,7 +272,7 @@ pub mod schema {
       }
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct SearchedCourse {
         pub term_code: String,
@@ -145,30 +286,44 @@ pub mod schema {
         pub credit_range: String,
         pub first_taught: Option<String>,
         pub last_taught: Option<String>,
-        pub typically_offered: String, // TODO useful information
+        /// Free text; see [`SearchedCourse::offered`].
+        pub typically_offered: String,
         pub general_ed: Option<ReqGoalAbbrev>,
         pub ethnic_studies: Option<ReqGoalAbbrev>,
         pub breadths: Vec<ReqGoalAbbrev>,
         pub letters_and_science_credits: Option<ReqGoalAbbrev>,
         pub workplace_experience: Option<ReqGoalAbbrev>,
         pub foreign_language: Option<ReqGoalAbbrev>,
-        // honors: Option<?>,
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub honors: Option<Honors>,
         pub levels: Vec<ReqGoalAbbrev>,
         pub open_to_first_year: bool,
-        // advisory_prerequisites: Option<AdvisoryPrerequisite>,
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub advisory_prerequisites: Option<String>,
         pub enrollment_prerequisites: Option<String>,
-        // all_cross_listed_subjets: Vec<?>,
+        /// Every subject the course is listed under, including its own.
+        #[serde(default, deserialize_with = "lenient::vec")]
+        pub all_cross_listed_subjects: Vec<section::schema::Subject>,
         pub title: String,
         pub description: String,
         pub catalog_print_flag: bool,
-        // academic_group_code: Vec<AcademicGroupCode>,
+        /// The schools/colleges offering the course, e.g. `"L&S"`.
+        #[serde(default, deserialize_with = "lenient::vec")]
+        pub academic_group_code: Vec<String>,
         pub currently_taught: bool,
-        // grading_basis: GradingBasis,
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub grading_basis: Option<GradingBasis>,
         pub repeatable: String,
-        // grad_course_work: Option<?>,
-        // sustainability: Option<?>,
-        // instructor_provided_content: Option<?>,
-        // course_requirements: CourseRequirements,
+        /// Whether the course counts towards graduate coursework requirements.
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub grad_course_work: Option<bool>,
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub sustainability: Option<ReqGoalAbbrev>,
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub instructor_provided_content: Option<InstructorProvidedContent>,
+        /// Requirement groups, keyed by id, each a list of requirements.
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub course_requirements: Option<BTreeMap<String, Vec<String>>>,
         pub course_designation: String,
         pub course_designation_raw: String,
         pub full_course_designation: String,
@@ -181,7 +336,97 @@ pub mod schema {
         pub matched_queries: Option<Vec<String>>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    impl SearchedCourse {
+        /// How many credits the course can be taken for.
+        pub fn credits(&self) -> CreditRange {
+            CreditRange::new(self.minimum_credits, self.maximum_credits)
+        }
+
+        /// The course's topics whose title or description contains `text`, ignoring case.
+        pub fn topics_matching<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a Topic> {
+            let text = text.to_lowercase();
+            self.topics.iter().filter(move |topic| {
+                topic.short_description.to_lowercase().contains(&text)
+                    || topic.long_description.to_lowercase().contains(&text)
+            })
+        }
+
+        /// When the course is typically offered, parsed from `typically_offered`.
+        pub fn offered(&self) -> TypicallyOffered {
+            TypicallyOffered::parse(&self.typically_offered)
+        }
+
+        /// The designations of the other subjects the course is cross-listed under,
+        /// e.g. `["E C E 400"]` for `COMP SCI 400`.
+        pub fn cross_listed_designations(&self) -> Vec<String> {
+            self.all_cross_listed_subjects
+                .iter()
+                .filter(|s| s.subject_code != self.subject.subject_code)
+                .map(|s| format!("{} {}", s.short_description, self.catalog_number))
+                .collect()
+        }
+    }
+
+    /// How a course is graded, e.g. `OPT` ("Student Option").
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GradingBasis {
+        pub code: String,
+        pub description: String,
+    }
+
+    /// How a course relates to the honors program.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(from = "String", into = "String")]
+    pub enum Honors {
+        HonorsOnly,
+        AcceleratedHonors,
+        InstructorApproved,
+        /// A value we don't know of yet, kept as the API sent it.
+        Other(String),
+    }
+
+    impl From<String> for Honors {
+        fn from(s: String) -> Self {
+            match s.as_str() {
+                "HONORS_ONLY" => Honors::HonorsOnly,
+                "ACCELERATED_HONORS" => Honors::AcceleratedHonors,
+                "INSTRUCTOR_APPROVED" => Honors::InstructorApproved,
+                _ => Honors::Other(s),
+            }
+        }
+    }
+
+    impl From<Honors> for String {
+        fn from(honors: Honors) -> Self {
+            match honors {
+                Honors::HonorsOnly => "HONORS_ONLY".to_string(),
+                Honors::AcceleratedHonors => "ACCELERATED_HONORS".to_string(),
+                Honors::InstructorApproved => "INSTRUCTOR_APPROVED".to_string(),
+                Honors::Other(s) => s,
+            }
+        }
+    }
+
+    /// What instructors wrote about a course, beyond its catalog description.
+    ///
+    /// Fields we don't know of are kept in `other`.
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct InstructorProvidedContent {
+        #[serde(default)]
+        pub learning_outcomes: Option<String>,
+        #[serde(default)]
+        pub typical_topics: Option<String>,
+        #[serde(default)]
+        pub format: Option<String>,
+        #[serde(default)]
+        pub last_updated: Option<u64>,
+        #[serde(flatten)]
+        pub other: BTreeMap<String, serde_json::Value>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Topic {
         pub short_description: String,
@@ -191,14 +436,14 @@ pub mod schema {
     }
 
     /// Links a title (seen in `input`) with the course id.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct TitleSuggestion {
         pub input: Vec<String>,
         pub payload: CourseIdObj,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct CourseIdObj {
         pub course_id: String,
@@ -206,7 +451,7 @@ pub mod schema {
 
     /// A pair
     /// This type of information seems similar to that displayed by DARS audits.
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ReqGoalAbbrev {
         pub code: String,
@@ -0,0 +1,120 @@
+//! When courses are typically offered.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::ParseFilterError;
+
+/// A part of the academic year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Season {
+    Fall,
+    Spring,
+    Summer,
+}
+
+impl Season {
+    const ALL: [Season; 3] = [Season::Fall, Season::Spring, Season::Summer];
+}
+
+impl FromStr for Season {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Season::ALL
+            .into_iter()
+            .find(|season| season.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseFilterError::new("season", s))
+    }
+}
+
+impl Display for Season {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Season::Fall => write!(f, "Fall"),
+            Season::Spring => write!(f, "Spring"),
+            Season::Summer => write!(f, "Summer"),
+        }
+    }
+}
+
+/// Which years a course is offered in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Cadence {
+    #[default]
+    EveryYear,
+    /// Every other year, without saying which.
+    AlternateYears,
+    OddYears,
+    EvenYears,
+}
+
+/// A course's `typically_offered` text, e.g. `"Fall, Spring, Summer"` or `"Fall (odd years)"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypicallyOffered {
+    Seasons {
+        /// In the order of the academic year, without duplicates.
+        seasons: Vec<Season>,
+        cadence: Cadence,
+    },
+    /// Offered every now and then, on no set schedule.
+    Occasionally,
+    /// Text we couldn't make sense of, kept as the API sent it.
+    Unknown(String),
+}
+
+impl TypicallyOffered {
+    /// Parses the API's text; this never fails, falling back to [`TypicallyOffered::Unknown`].
+    pub fn parse(text: &str) -> Self {
+        let lower = text.to_lowercase();
+        if lower.contains("occasional") {
+            return TypicallyOffered::Occasionally;
+        }
+
+        let seasons: Vec<Season> = Season::ALL
+            .into_iter()
+            .filter(|season| lower.contains(&season.to_string().to_lowercase()))
+            .collect();
+        if seasons.is_empty() {
+            return TypicallyOffered::Unknown(text.to_string());
+        }
+
+        let cadence = if lower.contains("odd") {
+            Cadence::OddYears
+        } else if lower.contains("even") {
+            Cadence::EvenYears
+        } else if lower.contains("alternat") || lower.contains("every other") {
+            Cadence::AlternateYears
+        } else {
+            Cadence::EveryYear
+        };
+        TypicallyOffered::Seasons { seasons, cadence }
+    }
+
+    /// Whether the course is known to be offered in `season` (in at least some years).
+    pub fn includes(&self, season: Season) -> bool {
+        match self {
+            TypicallyOffered::Seasons { seasons, .. } => seasons.contains(&season),
+            TypicallyOffered::Occasionally | TypicallyOffered::Unknown(_) => false,
+        }
+    }
+}
+
+impl Display for TypicallyOffered {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypicallyOffered::Seasons { seasons, cadence } => {
+                let seasons: Vec<String> = seasons.iter().map(Season::to_string).collect();
+                write!(f, "{}", seasons.join(", "))?;
+                match cadence {
+                    Cadence::EveryYear => Ok(()),
+                    Cadence::AlternateYears => write!(f, " (alternate years)"),
+                    Cadence::OddYears => write!(f, " (odd years)"),
+                    Cadence::EvenYears => write!(f, " (even years)"),
+                }
+            }
+            TypicallyOffered::Occasionally => write!(f, "Occasionally"),
+            TypicallyOffered::Unknown(text) => write!(f, "{}", text),
+        }
+    }
+}
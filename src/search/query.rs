@@ -0,0 +1,617 @@
+//! Typed search requests.
+
+use std::borrow::Borrow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::ser::{Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::credits::CreditFilter;
+use crate::error::ParseFilterError;
+use crate::search::offered::Season;
+use crate::search::schema::{ReqGoalAbbrev, SearchedCourse};
+use crate::section::filter::{ModeFilter, PackageFilter};
+use crate::section::schema::{CourseSection, Subject};
+use crate::{CourseStatusFilters, DEFAULT_PAGE_SIZE};
+
+/// How search results are ordered.
+///
+/// Most orders are applied by the API, so they hold across pages. [`SortOrder::Credits`] and
+/// [`SortOrder::Title`] aren't supported by the API; results are fetched by relevance and sorted
+/// once we have them (see [`SortOrder::sort`]), so a single page is only sorted within itself,
+/// while [`search_all`](crate::search::search_all) sorts every result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// By relevance to the query string.
+    #[default]
+    Score,
+    /// By subject, then catalog number.
+    Subject,
+    /// By catalog number, then subject.
+    CatalogNumber,
+    /// Most recently taught first.
+    LastTaught,
+    /// Fewest credits first.
+    Credits,
+    /// Alphabetically by title.
+    Title,
+}
+
+impl SortOrder {
+    const ALL: [SortOrder; 6] = [
+        SortOrder::Score,
+        SortOrder::Subject,
+        SortOrder::CatalogNumber,
+        SortOrder::LastTaught,
+        SortOrder::Credits,
+        SortOrder::Title,
+    ];
+
+    /// The `sortOrder` sent to the API.
+    pub fn api_value(&self) -> &'static str {
+        match self {
+            SortOrder::Subject => "SUBJECT",
+            SortOrder::CatalogNumber => "CATALOG_NUMBER",
+            SortOrder::LastTaught => "LAST_TAUGHT",
+            SortOrder::Score | SortOrder::Credits | SortOrder::Title => "SCORE",
+        }
+    }
+
+    /// Whether the API sorts the results itself.
+    pub fn is_server_side(&self) -> bool {
+        !matches!(self, SortOrder::Credits | SortOrder::Title)
+    }
+
+    /// Sorts courses we already have (owned or borrowed); the sort is stable, so ties keep the
+    /// order they came in.
+    ///
+    /// This is a no-op for [`SortOrder::Score`], since relevance is only known to whoever ranked
+    /// the courses.
+    pub fn sort<C: Borrow<SearchedCourse>>(&self, courses: &mut [C]) {
+        match self {
+            SortOrder::Score => {}
+            SortOrder::Subject => courses.sort_by(|a, b| {
+                let (a, b) = (a.borrow(), b.borrow());
+                (&a.subject.short_description, &a.catalog_sort)
+                    .cmp(&(&b.subject.short_description, &b.catalog_sort))
+            }),
+            SortOrder::CatalogNumber => courses.sort_by(|a, b| {
+                let (a, b) = (a.borrow(), b.borrow());
+                (&a.catalog_sort, &a.subject.short_description)
+                    .cmp(&(&b.catalog_sort, &b.subject.short_description))
+            }),
+            // Term codes grow over time, and courses never taught go last.
+            SortOrder::LastTaught => {
+                courses.sort_by(|a, b| b.borrow().last_taught.cmp(&a.borrow().last_taught))
+            }
+            SortOrder::Credits => courses.sort_by_key(|c| {
+                let c = c.borrow();
+                (c.minimum_credits, c.maximum_credits)
+            }),
+            SortOrder::Title => courses.sort_by_key(|c| c.borrow().title.to_lowercase()),
+        }
+    }
+}
+
+/// Parses names like `relevance`, `catalog-number` or `title`.
+impl FromStr for SortOrder {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted = normalize_code(s);
+        if wanted == "RELEVANCE" {
+            return Ok(SortOrder::Score);
+        }
+        SortOrder::ALL
+            .into_iter()
+            .find(|order| normalize_code(&order.to_string()) == wanted)
+            .ok_or_else(|| ParseFilterError::new("sort order", s))
+    }
+}
+
+impl Display for SortOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortOrder::Score => write!(f, "score"),
+            SortOrder::Subject => write!(f, "subject"),
+            SortOrder::CatalogNumber => write!(f, "catalog-number"),
+            SortOrder::LastTaught => write!(f, "last-taught"),
+            SortOrder::Credits => write!(f, "credits"),
+            SortOrder::Title => write!(f, "title"),
+        }
+    }
+}
+
+/// Restricts a search to one subject, given either by code or by short description.
+///
+/// Parsing treats all-digit input as a code (`"266"`) and anything else as a name
+/// (`"COMP SCI"`). Names are compared ignoring case and whitespace, so `"compsci"` works too.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SubjectFilter {
+    Code(String),
+    Name(String),
+}
+
+impl SubjectFilter {
+    pub fn matches(&self, subject: &Subject) -> bool {
+        match self {
+            SubjectFilter::Code(code) => subject.subject_code == *code,
+            SubjectFilter::Name(name) => {
+                normalize_subject_name(&subject.short_description) == normalize_subject_name(name)
+            }
+        }
+    }
+}
+
+impl FromStr for SubjectFilter {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+            Ok(SubjectFilter::Code(s.to_string()))
+        } else {
+            Ok(SubjectFilter::Name(s.to_string()))
+        }
+    }
+}
+
+impl Display for SubjectFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubjectFilter::Code(code) => write!(f, "{}", code),
+            SubjectFilter::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Uppercases a subject name and strips its whitespace, so `"Comp Sci"` equals `"COMP SCI"`.
+pub(crate) fn normalize_subject_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// A breadth requirement a course can count towards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Breadth {
+    Biological,
+    Humanities,
+    Literature,
+    /// Also satisfied by [`Breadth::Biological`] and [`Breadth::Physical`] courses.
+    NaturalScience,
+    Physical,
+    SocialScience,
+    Interdivisional,
+}
+
+impl Breadth {
+    const ALL: [Breadth; 7] = [
+        Breadth::Biological,
+        Breadth::Humanities,
+        Breadth::Literature,
+        Breadth::NaturalScience,
+        Breadth::Physical,
+        Breadth::SocialScience,
+        Breadth::Interdivisional,
+    ];
+
+    /// The code the API uses, e.g. `"N"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Breadth::Biological => "B",
+            Breadth::Humanities => "H",
+            Breadth::Literature => "L",
+            Breadth::NaturalScience => "N",
+            Breadth::Physical => "P",
+            Breadth::SocialScience => "S",
+            Breadth::Interdivisional => "Z",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Breadth::Biological => "Biological Science",
+            Breadth::Humanities => "Humanities",
+            Breadth::Literature => "Literature",
+            Breadth::NaturalScience => "Natural Science",
+            Breadth::Physical => "Physical Science",
+            Breadth::SocialScience => "Social Science",
+            Breadth::Interdivisional => "Interdivisional",
+        }
+    }
+
+    /// Every breadth code that satisfies this breadth.
+    fn satisfied_by(&self) -> &'static [&'static str] {
+        match self {
+            Breadth::NaturalScience => &["N", "B", "P"],
+            Breadth::Biological => &["B"],
+            Breadth::Humanities => &["H"],
+            Breadth::Literature => &["L"],
+            Breadth::Physical => &["P"],
+            Breadth::SocialScience => &["S"],
+            Breadth::Interdivisional => &["Z"],
+        }
+    }
+
+    pub fn matches(&self, breadths: &[ReqGoalAbbrev]) -> bool {
+        breadths
+            .iter()
+            .any(|b| self.satisfied_by().contains(&b.code.as_str()))
+    }
+}
+
+/// Parses a code (`"N"`) or a description (`"natural science"`).
+impl FromStr for Breadth {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted = normalize_code(s);
+        Breadth::ALL
+            .into_iter()
+            .find(|b| wanted == b.code() || wanted == normalize_code(b.description()))
+            .ok_or_else(|| ParseFilterError::new("breadth", s))
+    }
+}
+
+impl Display for Breadth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// How advanced a course is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Level {
+    Elementary,
+    Intermediate,
+    Advanced,
+}
+
+impl Level {
+    const ALL: [Level; 3] = [Level::Elementary, Level::Intermediate, Level::Advanced];
+
+    /// The code the API uses, e.g. `"A"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Level::Elementary => "E",
+            Level::Intermediate => "I",
+            Level::Advanced => "A",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Level::Elementary => "Elementary",
+            Level::Intermediate => "Intermediate",
+            Level::Advanced => "Advanced",
+        }
+    }
+
+    pub fn matches(&self, levels: &[ReqGoalAbbrev]) -> bool {
+        levels.iter().any(|l| l.code == self.code())
+    }
+}
+
+/// Parses a code (`"A"`) or a description (`"advanced"`).
+impl FromStr for Level {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted = normalize_code(s);
+        Level::ALL
+            .into_iter()
+            .find(|l| wanted == l.code() || wanted == normalize_code(l.description()))
+            .ok_or_else(|| ParseFilterError::new("level", s))
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// Whether a requirement (e.g. `general_ed`) has the code or description `wanted`, ignoring case
+/// and punctuation, so `"COM-B"` matches a code of `"COM B"`.
+fn requirement_matches(requirement: &ReqGoalAbbrev, wanted: &str) -> bool {
+    let wanted = normalize_code(wanted);
+    normalize_code(&requirement.code) == wanted
+        || normalize_code(&requirement.description) == wanted
+}
+
+/// Uppercases a code and strips everything but letters and digits.
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// A course search, serialized into the (Elasticsearch-style) body the search endpoint expects.
+///
+/// ```
+/// use madpinger::search::query::SearchQuery;
+/// use madpinger::CourseStatusFilters;
+///
+/// let query = SearchQuery::new("1242")
+///     .query("calculus")
+///     .statuses(CourseStatusFilters::new(true, true, false))
+///     .page_size(25);
+/// let payload = serde_json::to_string(&query).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub term_code: String,
+    /// Free text matched against titles, descriptions, designations, etc.
+    pub query: String,
+    /// Which enrollment statuses a course needs a package in; if none are set, any status goes.
+    pub statuses: CourseStatusFilters,
+    /// Starts at 1.
+    pub page: usize,
+    pub page_size: usize,
+    pub sort_order: SortOrder,
+    /// If any are given, only courses in one of these subjects are returned.
+    ///
+    /// Codes are filtered on by the API; names can only be checked against the results (see
+    /// [`SearchQuery::matches`]), so resolve them first with
+    /// [`resolve_subjects`](crate::search::resolve_subjects).
+    pub subjects: Vec<SubjectFilter>,
+    /// If any are given, only courses counting towards one of these breadths are returned.
+    pub breadths: Vec<Breadth>,
+    /// If any are given, only courses meeting one of these general education requirements
+    /// (by code or description, e.g. `"COM B"`) are returned. Only checked on our side.
+    pub general_ed: Vec<String>,
+    /// Only return courses that meet the ethnic studies requirement.
+    pub ethnic_studies: bool,
+    /// If any are given, only courses at one of these levels are returned.
+    pub levels: Vec<Level>,
+    /// Only return courses that can be taken for a number of credits within these bounds.
+    pub credits: CreditFilter,
+    /// How a course needs a package to be taught in; only checked by the API.
+    pub modes: ModeFilter,
+    /// If any are given, only courses typically offered in one of these seasons are returned.
+    /// Only checked on our side; courses offered occasionally (or at unknown times) never pass.
+    pub offered: Vec<Season>,
+    /// Only return special topics courses with a topic whose title or description contains this
+    /// text. Only checked on our side.
+    pub topic: Option<String>,
+}
+
+impl SearchQuery {
+    /// Searches every course in a term.
+    pub fn new(term_code: impl Into<String>) -> Self {
+        Self {
+            term_code: term_code.into(),
+            query: "*".to_string(),
+            statuses: CourseStatusFilters::default(),
+            page: 1,
+            page_size: DEFAULT_PAGE_SIZE,
+            sort_order: SortOrder::default(),
+            subjects: Vec::new(),
+            breadths: Vec::new(),
+            general_ed: Vec::new(),
+            ethnic_studies: false,
+            levels: Vec::new(),
+            credits: CreditFilter::default(),
+            modes: ModeFilter::default(),
+            offered: Vec::new(),
+            topic: None,
+        }
+    }
+
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = query.into();
+        self
+    }
+
+    pub fn statuses(mut self, statuses: CourseStatusFilters) -> Self {
+        self.statuses = statuses;
+        self
+    }
+
+    pub fn page(mut self, page: usize) -> Self {
+        self.page = page.max(1);
+        self
+    }
+
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub fn sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = sort_order;
+        self
+    }
+
+    /// Adds a subject to search in.
+    pub fn subject(mut self, subject: SubjectFilter) -> Self {
+        self.subjects.push(subject);
+        self
+    }
+
+    pub fn subjects(mut self, subjects: impl IntoIterator<Item = SubjectFilter>) -> Self {
+        self.subjects.extend(subjects);
+        self
+    }
+
+    pub fn breadth(mut self, breadth: Breadth) -> Self {
+        self.breadths.push(breadth);
+        self
+    }
+
+    pub fn general_ed(mut self, general_ed: impl Into<String>) -> Self {
+        self.general_ed.push(general_ed.into());
+        self
+    }
+
+    pub fn ethnic_studies(mut self, ethnic_studies: bool) -> Self {
+        self.ethnic_studies = ethnic_studies;
+        self
+    }
+
+    pub fn level(mut self, level: Level) -> Self {
+        self.levels.push(level);
+        self
+    }
+
+    pub fn credits(mut self, credits: CreditFilter) -> Self {
+        self.credits = credits;
+        self
+    }
+
+    pub fn modes(mut self, modes: ModeFilter) -> Self {
+        self.modes = modes;
+        self
+    }
+
+    pub fn offered(mut self, season: Season) -> Self {
+        self.offered.push(season);
+        self
+    }
+
+    pub fn topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = Some(topic.into());
+        self
+    }
+
+    /// Whether a course satisfies the parts of this query that are checked on our side.
+    ///
+    /// Search results are run through this, since not every filter can be expressed to the API.
+    pub fn matches(&self, course: &SearchedCourse) -> bool {
+        let subject =
+            self.subjects.is_empty() || self.subjects.iter().any(|s| s.matches(&course.subject));
+        let breadth =
+            self.breadths.is_empty() || self.breadths.iter().any(|b| b.matches(&course.breadths));
+        let general_ed = self.general_ed.is_empty()
+            || course.general_ed.as_ref().map_or(false, |g| {
+                self.general_ed.iter().any(|w| requirement_matches(g, w))
+            });
+        let ethnic_studies = !self.ethnic_studies || course.ethnic_studies.is_some();
+        let level = self.levels.is_empty() || self.levels.iter().any(|l| l.matches(&course.levels));
+
+        let credits = self.credits.matches(&course.credits());
+        let offered = self.offered.is_empty() || {
+            let typically = course.offered();
+            self.offered
+                .iter()
+                .any(|season| typically.includes(*season))
+        };
+
+        let topic = match &self.topic {
+            Some(topic) => course.topics_matching(topic).next().is_some(),
+            None => true,
+        };
+
+        subject && breadth && general_ed && ethnic_studies && level && credits && offered && topic
+    }
+
+    /// Whether a package has one of this query's statuses, is taught in one of its modes and
+    /// passes `filter`, all at once; a course is only a match if a single package passes.
+    pub fn matches_package(&self, package: &CourseSection, filter: &PackageFilter) -> bool {
+        self.statuses
+            .matches(&package.package_enrollment_status.status)
+            && self.modes.matches(package)
+            && filter.matches(package)
+    }
+
+    /// The filters applied to a course's enrollment packages.
+    fn package_filters(&self) -> Vec<Value> {
+        let mut must = Vec::new();
+
+        let statuses: Vec<&str> = [
+            (self.statuses.open, "OPEN"),
+            (self.statuses.waitlisted, "WAITLISTED"),
+            (self.statuses.closed, "CLOSED"),
+        ]
+        .iter()
+        .filter(|(wanted, _)| *wanted)
+        .map(|(_, status)| *status)
+        .collect();
+        if !statuses.is_empty() {
+            must.push(json!({ "match": { "packageEnrollmentStatus.status": statuses.join(" ") } }));
+        }
+
+        must.extend(self.modes.to_query());
+        must.push(json!({ "match": { "published": true } }));
+        must
+    }
+
+    /// The `filters` of the request body.
+    fn filters(&self) -> Vec<Value> {
+        let mut filters = vec![json!({
+            "has_child": {
+                "type": "enrollmentPackage",
+                "query": { "bool": { "must": self.package_filters() } }
+            }
+        })];
+
+        let codes: Vec<&str> = self
+            .subjects
+            .iter()
+            .filter_map(|s| match s {
+                SubjectFilter::Code(code) => Some(code.as_str()),
+                SubjectFilter::Name(_) => None,
+            })
+            .collect();
+        // A name that couldn't be sent would otherwise be shadowed by the codes.
+        let all_codes = codes.len() == self.subjects.len();
+        if !codes.is_empty() && all_codes {
+            filters.push(json!({ "terms": { "subject.subjectCode": codes } }));
+        }
+
+        if !self.breadths.is_empty() {
+            let mut codes: Vec<&str> = self
+                .breadths
+                .iter()
+                .flat_map(|b| b.satisfied_by().iter().copied())
+                .collect();
+            codes.sort_unstable();
+            codes.dedup();
+            filters.push(json!({ "terms": { "breadths.code": codes } }));
+        }
+        if self.ethnic_studies {
+            filters.push(json!({ "exists": { "field": "ethnicStudies" } }));
+        }
+        if !self.levels.is_empty() {
+            let codes: Vec<&str> = self.levels.iter().map(Level::code).collect();
+            filters.push(json!({ "terms": { "levels.code": codes } }));
+        }
+        if let Some(lower) = self.credits.lower() {
+            filters.push(json!({ "range": { "maximumCredits": { "gte": lower } } }));
+        }
+        if let Some(upper) = self.credits.upper() {
+            filters.push(json!({ "range": { "minimumCredits": { "lte": upper } } }));
+        }
+
+        filters
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Payload<'a> {
+    selected_term: &'a str,
+    query_string: &'a str,
+    filters: Vec<Value>,
+    page: usize,
+    page_size: usize,
+    sort_order: &'static str,
+}
+
+impl Serialize for SearchQuery {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Payload {
+            selected_term: &self.term_code,
+            query_string: &self.query,
+            filters: self.filters(),
+            page: self.page,
+            page_size: self.page_size,
+            sort_order: self.sort_order.api_value(),
+        }
+        .serialize(serializer)
+    }
+}
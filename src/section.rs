@@ -2,20 +2,13 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
-use reqwest::get;
-use reqwest::header::HeaderMap;
-use reqwest::header::HeaderValue;
-use reqwest::header::ACCEPT;
-use reqwest::header::ACCEPT_ENCODING;
-use reqwest::header::ACCEPT_LANGUAGE;
-use reqwest::header::CONNECTION;
-use reqwest::header::CONTENT_LENGTH;
-use reqwest::header::CONTENT_TYPE;
-use reqwest::Client;
-use reqwest::Error as ReqwestError;
-use std::fs::File;
-use std::io::Write;
+use std::fmt::{Display, Formatter};
 
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::client::{ApiRequest, CseClient, Endpoint};
+use crate::error::{decode, MadpingerError};
+use crate::search::schema::SearchedCourse;
 use crate::section::schema::{
     CatalogRequirementGroups, CourseSection, EnrollmentOptions, EnrollmentStatus, MeetingMap,
     PackageEnrollmentStatus, Status,
@@ -23,41 +16,155 @@ use crate::section::schema::{
 
 pub const SECTION_GET_URI_BASE: &str =
     "https://public.enroll.wisc.edu/api/search/v1/enrollmentPackages";
+/// Path of the enrollment packages endpoint, relative to the client's base URL.
+pub const SECTION_GET_PATH: &str = "/api/search/v1/enrollmentPackages";
+pub mod breakdown;
+pub mod cross_listing;
+pub mod filter;
+
+/// How many courses [`get_sections_bulk`] fetches at once by default.
+pub const DEFAULT_BULK_CONCURRENCY: usize = 8;
+
+/// Identifies a course by its term code, subject code and course id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CourseKey {
+    pub term_code: String,
+    pub subject_code: String,
+    pub course_id: String,
+}
+
+impl<T, S, C> From<(T, S, C)> for CourseKey
+where
+    T: Into<String>,
+    S: Into<String>,
+    C: Into<String>,
+{
+    fn from((term_code, subject_code, course_id): (T, S, C)) -> Self {
+        Self {
+            term_code: term_code.into(),
+            subject_code: subject_code.into(),
+            course_id: course_id.into(),
+        }
+    }
+}
+
+impl From<&SearchedCourse> for CourseKey {
+    fn from(course: &SearchedCourse) -> Self {
+        Self::from((
+            course.term_code.as_str(),
+            course.subject.subject_code.as_str(),
+            course.course_id.as_str(),
+        ))
+    }
+}
+
+impl Display for CourseKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.term_code, self.subject_code, self.course_id
+        )
+    }
+}
+
+/// How an enrollment package is taught, derived from its `online_only`, `is_asynchronous` and
+/// `modes_of_instruction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeliveryMode {
+    InPerson,
+    /// Online, meeting at scheduled times.
+    Online,
+    /// Online, without scheduled meetings.
+    Asynchronous,
+    /// Partly in a classroom, partly online.
+    Hybrid,
+}
+
+impl DeliveryMode {
+    pub fn of(package: &CourseSection) -> Self {
+        if package.is_asynchronous {
+            DeliveryMode::Asynchronous
+        } else if package.online_only {
+            DeliveryMode::Online
+        } else if package
+            .modes_of_instruction
+            .iter()
+            .any(|mode| mode.to_lowercase().contains("online"))
+        {
+            DeliveryMode::Hybrid
+        } else {
+            DeliveryMode::InPerson
+        }
+    }
+
+    /// Whether the package can be taken without coming to campus.
+    pub fn is_online(&self) -> bool {
+        matches!(self, DeliveryMode::Online | DeliveryMode::Asynchronous)
+    }
+}
+
+impl Display for DeliveryMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryMode::InPerson => write!(f, "in person"),
+            DeliveryMode::Online => write!(f, "online"),
+            DeliveryMode::Asynchronous => write!(f, "online, async"),
+            DeliveryMode::Hybrid => write!(f, "hybrid"),
+        }
+    }
+}
 
 /// Retrieves the sections of a course that can be identified with the params.
 pub async fn get_section_info(
-    client: &Client,
+    client: &CseClient,
     term_code: &str,
     subject_code: &str,
     course_id: &str,
-) -> Result<Vec<CourseSection>, ReqwestError> {
-    let url = format!(
-        "{}/{}/{}/{}",
-        SECTION_GET_URI_BASE, term_code, subject_code, course_id
-    );
-
-    let mut hdrs = HeaderMap::new();
-    hdrs.insert(ACCEPT, HeaderValue::from_static("application/json")); // not required
-    hdrs.insert(
-        ACCEPT_ENCODING,
-        HeaderValue::from_static("gzip, deflate, br"),
-    ); // not required
-    hdrs.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.5")); // not required
-    hdrs.insert(CONNECTION, HeaderValue::from_static("keep-alive"));
-
-    let resp = client.get(url).headers(hdrs).send().await?;
-
-    //let resp: reqwest::Response = get(url).await?;
-    let course_sections = resp.json::<Vec<CourseSection>>().await?;
-    Ok(course_sections)
+) -> Result<Vec<CourseSection>, MadpingerError> {
+    let url = client.sections_url(term_code, subject_code, course_id);
+    let body = client
+        .fetch(&ApiRequest::get(Endpoint::Sections, url.clone()))
+        .await?;
+
+    decode(&url, &body)
+}
+
+/// Retrieves the sections of many courses, fetching at most `concurrency` courses at once.
+///
+/// Results are yielded as they come in (not in the order of `keys`), and a failure for one course
+/// doesn't stop the others. Requests still go through the client's rate limiter and retry policy.
+pub fn get_sections_bulk<'a, I>(
+    client: &'a CseClient,
+    keys: I,
+    concurrency: usize,
+) -> impl Stream<Item = (CourseKey, Result<Vec<CourseSection>, MadpingerError>)> + 'a
+where
+    I: IntoIterator,
+    I::Item: Into<CourseKey> + 'a,
+    I::IntoIter: 'a,
+{
+    stream::iter(keys.into_iter().map(Into::into))
+        .map(move |key: CourseKey| async move {
+            let result =
+                get_section_info(client, &key.term_code, &key.subject_code, &key.course_id).await;
+            (key, result)
+        })
+        .buffer_unordered(concurrency.max(1))
 }
 
 pub mod schema {
+    use chrono::{NaiveDate, NaiveTime, Weekday};
+
+    use crate::credits::CreditRange;
+    use crate::lenient;
+    use crate::section::filter::Days;
     use crate::section::schema;
+    use crate::timestamp::{campus_date, campus_time};
     use serde::{Deserialize, Serialize};
     use std::fmt::{Display, Formatter};
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct CourseSection {
         pub id: String,
@@ -83,7 +190,50 @@ pub mod schema {
         pub doc_id: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    impl CourseSection {
+        /// How many credits the package can be taken for, if its `credit_range` makes sense.
+        pub fn credits(&self) -> Option<CreditRange> {
+            self.credit_range.parse().ok()
+        }
+
+        /// The topic the package covers, for special topics courses; taken from the first of its
+        /// sections that has one.
+        pub fn topic(&self) -> Option<&SectionTopic> {
+            self.sections
+                .iter()
+                .find_map(|section| section.topic.as_ref())
+        }
+
+        /// The package's regular meetings, leaving out exams (which `class_meetings` includes).
+        pub fn meetings(&self) -> impl Iterator<Item = &ClassMeeting> {
+            self.class_meetings
+                .iter()
+                .filter(|m| m.meeting_type == MeetingType::Class)
+        }
+
+        /// The package's course designation, e.g. `COMP SCI 400`; `None` without sections.
+        pub fn designation(&self) -> Option<String> {
+            let section = self.sections.first()?;
+            Some(format!(
+                "{} {}",
+                section.subject.short_description, self.catalog_number
+            ))
+        }
+
+        /// The classes the package is made of, by their primary class numbers (sorted); the
+        /// listings of a cross-listed package all have the same ones.
+        pub fn primary_class_numbers(&self) -> Vec<usize> {
+            let mut numbers: Vec<usize> = self
+                .sections
+                .iter()
+                .map(Section::primary_class_number)
+                .collect();
+            numbers.sort_unstable();
+            numbers
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct PackageEnrollmentStatus {
         pub available_seats: Option<usize>,
@@ -91,7 +241,7 @@ pub mod schema {
         pub status: Status,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct CrossListing {
         pub cross_listed_type: String,
@@ -99,7 +249,7 @@ pub mod schema {
         pub primary_subject: Option<Subject>, // TODO is this an optional?
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ClassMeeting {
         pub meeting_or_exam_number: String,
@@ -113,7 +263,91 @@ pub mod schema {
         pub exam_date: Option<u64>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    /// The topic of a section of a special topics course.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SectionTopic {
+        #[serde(default)]
+        pub id: Option<usize>,
+        /// The topic's title.
+        pub short_description: String,
+        #[serde(default)]
+        pub long_description: Option<String>,
+    }
+
+    impl ClassMeeting {
+        /// When the meeting starts, in campus time.
+        pub fn start_time(&self) -> Option<NaiveTime> {
+            self.meeting_time_start.and_then(campus_time)
+        }
+
+        /// When the meeting ends, in campus time.
+        pub fn end_time(&self) -> Option<NaiveTime> {
+            self.meeting_time_end.and_then(campus_time)
+        }
+
+        /// The days of the week the meeting is held on; days the API names oddly are skipped.
+        pub fn days(&self) -> Days {
+            self.meeting_days_list
+                .iter()
+                .filter_map(|day| day.parse::<Weekday>().ok())
+                .collect()
+        }
+
+        /// The day an exam is held on, in campus time.
+        pub fn exam_day(&self) -> Option<NaiveDate> {
+            self.exam_date.and_then(campus_date)
+        }
+
+        /// Where the meeting is held, e.g. `Van Vleck Hall B102`.
+        pub fn location(&self) -> Option<String> {
+            let building = self.building.as_ref().map(|b| b.building_name.as_str());
+            match (building, self.room.as_deref()) {
+                (Some(building), Some(room)) => Some(format!("{} {}", building, room)),
+                (Some(place), None) | (None, Some(place)) => Some(place.to_string()),
+                (None, None) => None,
+            }
+        }
+    }
+
+    /// Writes when and where the meeting is held, e.g. `MWF 9:55–10:45 @ Van Vleck Hall B102`,
+    /// or `exam Dec 17 19:45–21:45 @ ...` for exams; times are in campus time.
+    impl Display for ClassMeeting {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            let mut when = Vec::new();
+            if self.meeting_type == MeetingType::Exam {
+                when.push("exam".to_string());
+                when.extend(self.exam_day().map(|day| day.format("%b %-d").to_string()));
+            }
+            let days = self.days();
+            if !days.is_empty() {
+                when.push(days.to_string());
+            }
+            match (self.start_time(), self.end_time()) {
+                (Some(start), Some(end)) => when.push(format!(
+                    "{}–{}",
+                    start.format("%-H:%M"),
+                    end.format("%-H:%M")
+                )),
+                (Some(time), None) | (None, Some(time)) => {
+                    when.push(time.format("%-H:%M").to_string())
+                }
+                (None, None) => {}
+            }
+
+            let when = if when.is_empty() {
+                "TBA".to_string()
+            } else {
+                when.join(" ")
+            };
+            match self.location() {
+                Some(location) => write!(f, "{} @ {}", when, location),
+                None => write!(f, "{}", when),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     // TODO consider refactoring as enum (oncampus, offcampus: for off campus locations) to eliminate options
     pub struct Building {
@@ -125,12 +359,14 @@ pub mod schema {
         pub location: Option<Vec<f64>>, // (f64, f64)
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Section {
         pub class_unique_id: ClassUniqueId,
         pub published: bool,
-        // pub topic: Option<?>
+        /// What a special topics course covers in this section.
+        #[serde(default, deserialize_with = "lenient::option")]
+        pub topic: Option<SectionTopic>,
         pub start_date: u64,
         pub end_date: u64,
         pub active: bool,
@@ -158,14 +394,45 @@ pub mod schema {
         pub instructor: Option<Instructor>, // basically a wrapper type of PersonAttributes
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    impl Section {
+        /// The first day of classes, in campus time.
+        pub fn first_day(&self) -> Option<NaiveDate> {
+            campus_date(self.start_date)
+        }
+
+        /// The last day of classes, in campus time.
+        pub fn last_day(&self) -> Option<NaiveDate> {
+            campus_date(self.end_date)
+        }
+
+        /// The component the section is, e.g. `DIS 312`.
+        pub fn component(&self) -> String {
+            format!("{} {}", self.assembly_type, self.section_number)
+        }
+
+        pub fn is_cross_listed(&self) -> bool {
+            self.cross_listing.is_some()
+        }
+
+        /// The class number the section is enrolled under: the primary listing's, if the
+        /// section is cross-listed, and its own otherwise.
+        pub fn primary_class_number(&self) -> usize {
+            self.cross_listing
+                .as_ref()
+                .map_or(self.class_unique_id.class_number, |c| {
+                    c.primary_class_number
+                })
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ClassUniqueId {
         pub term_code: String,
         pub class_number: usize,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Subject {
         pub term_code: String,
@@ -183,7 +450,7 @@ pub mod schema {
         pub department_owner_academic_org_code: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct SchoolCollege {
         pub academic_org_code: String,
@@ -195,14 +462,14 @@ pub mod schema {
         pub school_college_uri: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Consent {
         pub code: String,
         pub description: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct EnrollmentStatus {
         pub class_unique_id: ClassUniqueId,
@@ -218,7 +485,7 @@ pub mod schema {
         pub aggregate_waitlist_current_size: Option<usize>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ClassMaterials {
         pub class_unique_id: ClassUniqueId,
@@ -231,7 +498,7 @@ pub mod schema {
         pub other_materials: Vec<EtcMaterials>, // TODO are these really Vec<String> types?
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct PersonAttributes {
         pub emplid: String,
@@ -244,7 +511,7 @@ pub mod schema {
         pub office365_primary_email: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct InstructorName {
         pub first: Option<String>,
@@ -254,13 +521,13 @@ pub mod schema {
         pub legal_middle: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Instructor {
         person_attributes: PersonAttributes,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct EnrollmentOptions {
         pub class_permission_number_needed: bool,
@@ -269,7 +536,7 @@ pub mod schema {
         pub related_class_number: bool,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct MeetingMap {
         pub monday: bool,
@@ -281,13 +548,13 @@ pub mod schema {
         pub sunday: bool,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct CatalogRequirementGroups {
         pub catalog_requirement_groups: Vec<CatalogRequirement>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct CatalogRequirement {
         pub code: String,
@@ -295,7 +562,7 @@ pub mod schema {
         // classAssociationRequirementGroups: Vec<String>, TODO type?
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Textbook {
         title: String,
@@ -308,7 +575,7 @@ pub mod schema {
         notes: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct InstructorProvidedClassDetails {
         pub class_unique_id: ClassUniqueId,
@@ -322,14 +589,14 @@ pub mod schema {
         pub last_updated: u64,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct LabeledUri {
         label: String,
         uri: String,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct EtcMaterials {
         description: String,
@@ -337,12 +604,16 @@ pub mod schema {
         notes: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
-    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    /// Codes the API sends that we don't know of are kept in `Other` (and logged), so a new
+    /// code doesn't break decoding.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(from = "String", into = "String")]
     pub enum Status {
         Open,
         Waitlisted,
         Closed,
+        /// A status we don't know of yet, kept as the API sent it.
+        Other(String),
     }
 
     impl Status {
@@ -352,25 +623,68 @@ pub mod schema {
         }
     }
 
+    impl From<String> for Status {
+        fn from(s: String) -> Self {
+            match s.as_str() {
+                "OPEN" => Self::Open,
+                "WAITLISTED" => Self::Waitlisted,
+                "CLOSED" => Self::Closed,
+                _ => {
+                    log::warn!("unknown enrollment status {:?}", s);
+                    Self::Other(s)
+                }
+            }
+        }
+    }
+
+    impl From<Status> for String {
+        fn from(status: Status) -> Self {
+            status.to_string()
+        }
+    }
+
     impl Display for Status {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             write!(
                 f,
                 "{}",
-                match *self {
+                match self {
                     Self::Open => "OPEN",
                     Self::Waitlisted => "WAITLISTED",
                     Self::Closed => "CLOSED",
+                    Self::Other(s) => s,
                 }
             )
         }
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
-    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    /// Codes we don't know of are kept in `Other`, like [`Status`].
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(from = "String", into = "String")]
     pub enum MeetingType {
         Class,
         Exam,
+        /// A meeting type we don't know of yet, kept as the API sent it.
+        Other(String),
+    }
+
+    impl From<String> for MeetingType {
+        fn from(s: String) -> Self {
+            match s.as_str() {
+                "CLASS" => Self::Class,
+                "EXAM" => Self::Exam,
+                _ => {
+                    log::warn!("unknown meeting type {:?}", s);
+                    Self::Other(s)
+                }
+            }
+        }
+    }
+
+    impl From<MeetingType> for String {
+        fn from(meeting_type: MeetingType) -> Self {
+            meeting_type.to_string()
+        }
     }
 
     impl Display for MeetingType {
@@ -378,16 +692,18 @@ pub mod schema {
             write!(
                 f,
                 "{}",
-                match *self {
+                match self {
                     Self::Class => "CLASS",
                     Self::Exam => "EXAM",
+                    Self::Other(s) => s,
                 }
             )
         }
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
-    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    /// Codes we don't know of are kept in `Other`, like [`Status`].
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(from = "String", into = "String")]
     pub enum AssemblyType {
         /// Lecture
         Lec,
@@ -401,6 +717,47 @@ pub mod schema {
         Ind,
         /// Seminar
         Sem,
+        /// A component we don't know of yet (e.g. `CLN`), kept as the API sent it.
+        Other(String),
+    }
+
+    impl AssemblyType {
+        /// What the component is called in prose, e.g. `lecture`; unknown components go by
+        /// their code.
+        pub fn name(&self) -> &str {
+            match self {
+                Self::Lec => "lecture",
+                Self::Dis => "discussion",
+                Self::Lab => "lab",
+                Self::Fld => "field work",
+                Self::Ind => "independent study",
+                Self::Sem => "seminar",
+                Self::Other(s) => s,
+            }
+        }
+    }
+
+    impl From<String> for AssemblyType {
+        fn from(s: String) -> Self {
+            match s.as_str() {
+                "LEC" => Self::Lec,
+                "DIS" => Self::Dis,
+                "LAB" => Self::Lab,
+                "FLD" => Self::Fld,
+                "IND" => Self::Ind,
+                "SEM" => Self::Sem,
+                _ => {
+                    log::warn!("unknown assembly type {:?}", s);
+                    Self::Other(s)
+                }
+            }
+        }
+    }
+
+    impl From<AssemblyType> for String {
+        fn from(assembly_type: AssemblyType) -> Self {
+            assembly_type.to_string()
+        }
     }
 
     impl Display for AssemblyType {
@@ -408,13 +765,14 @@ pub mod schema {
             write!(
                 f,
                 "{}",
-                match *self {
+                match self {
                     Self::Lec => "LEC",
                     Self::Dis => "DIS",
                     Self::Lab => "LAB",
                     Self::Fld => "FLD",
                     Self::Ind => "IND",
                     Self::Sem => "SEM",
+                    Self::Other(s) => s,
                 }
             )
         }
@@ -0,0 +1,59 @@
+//! Breaking an enrollment package down into its components.
+//!
+//! A package (e.g. `LEC 001` + `DIS 312`) only has seats while every one of its components does,
+//! so a package can be closed even though its lecture has plenty of room.
+
+use std::fmt::{Display, Formatter};
+
+use crate::section::schema::{CourseSection, Section};
+
+/// The component of a package that limits enrollment the most.
+#[derive(Debug, Clone, Copy)]
+pub struct Bottleneck<'a> {
+    /// The component with the fewest open seats (then the fewest open waitlist spots).
+    pub section: &'a Section,
+    /// The component with the most open seats, to contrast it with.
+    pub roomiest: &'a Section,
+}
+
+impl<'a> Bottleneck<'a> {
+    /// The bottleneck of a package; `None` if it has no components.
+    pub fn of(package: &'a CourseSection) -> Option<Self> {
+        let seats = |s: &&Section| {
+            (
+                s.enrollment_status.open_seats,
+                s.enrollment_status.open_waitlist_spots,
+            )
+        };
+        let section = package.sections.iter().min_by_key(seats)?;
+        let roomiest = package.sections.iter().max_by_key(seats)?;
+        Some(Self { section, roomiest })
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.section.enrollment_status.open_seats == 0
+    }
+}
+
+/// Explains the bottleneck, e.g. `lecture has 40 open seats but DIS 312 is full`.
+impl Display for Bottleneck<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let open = self.section.enrollment_status.open_seats;
+        let roomiest_open = self.roomiest.enrollment_status.open_seats;
+        if roomiest_open > open {
+            write!(
+                f,
+                "{} has {} open seats but ",
+                self.roomiest.assembly_type.name(),
+                roomiest_open
+            )?;
+        }
+
+        let component = self.section.component();
+        match open {
+            0 => write!(f, "{} is full", component),
+            _ if roomiest_open > open => write!(f, "{} only has {}", component, open),
+            _ => write!(f, "{} has {} open seats", component, open),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Counting the listings of a cross-listed package once.
+//!
+//! A package cross-listed under several subjects (e.g. `COMP SCI 400` and `E C E 400`) comes back
+//! once per listing, and the listings usually report the same, shared seats; adding them up
+//! counts those seats once per listing.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::section::schema::{CourseSection, EnrollmentStatus, Section};
+
+/// Groups the packages that are listings of the same classes, in the order they first appear.
+pub fn group_cross_listed(packages: &[CourseSection]) -> Vec<Vec<&CourseSection>> {
+    let mut groups: Vec<Vec<&CourseSection>> = Vec::new();
+    let mut seen: HashMap<Vec<usize>, usize> = HashMap::new();
+    for package in packages {
+        let classes = package.primary_class_numbers();
+        // packages without sections can't be told apart, so they're never grouped
+        match seen.get(&classes).filter(|_| !classes.is_empty()) {
+            Some(&i) => groups[i].push(package),
+            None => {
+                seen.insert(classes, groups.len());
+                groups.push(vec![package]);
+            }
+        }
+    }
+    groups
+}
+
+/// Every designation of a group of listings, e.g. `["COMP SCI 400", "E C E 400"]`.
+pub fn designations(listings: &[&CourseSection]) -> Vec<String> {
+    let mut designations: Vec<String> = Vec::new();
+    for designation in listings.iter().filter_map(|p| p.designation()) {
+        if !designations.contains(&designation) {
+            designations.push(designation);
+        }
+    }
+    designations
+}
+
+/// Where the counts of a [`CombinedEnrollment`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnrollmentSource {
+    /// Every count is an aggregate over the listings, as reported by the API.
+    Aggregate,
+    /// Every count is the listings' own, counted once per class.
+    Fallback,
+    /// Some of each.
+    Mix,
+}
+
+impl Display for EnrollmentSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnrollmentSource::Aggregate => write!(f, "AGG"),
+            EnrollmentSource::Fallback => write!(f, "FB"),
+            EnrollmentSource::Mix => write!(f, "MIX"),
+        }
+    }
+}
+
+/// The enrollment of a package across all of its listings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CombinedEnrollment {
+    pub capacity: usize,
+    pub enrolled: usize,
+    pub waitlist_capacity: usize,
+    pub waitlisted: usize,
+    pub source: EnrollmentSource,
+}
+
+impl CombinedEnrollment {
+    /// Combines the enrollment of a group of listings; `None` if none of them has any.
+    ///
+    /// The API's aggregate counts already cover every listing, so they're taken from the first
+    /// listing having them. Any count without an aggregate is summed over the classes the
+    /// listings enroll in, so listings of the same class (which share its seats) count once,
+    /// preferably as the primary listing reports it.
+    pub fn of(listings: &[&CourseSection]) -> Option<Self> {
+        let mut by_class: Vec<(usize, bool, &EnrollmentStatus)> = Vec::new();
+        for package in listings {
+            let status = match &package.enrollment_status {
+                Some(status) => status,
+                None => continue,
+            };
+            let own = status.class_unique_id.class_number;
+            let class = package
+                .sections
+                .iter()
+                .find(|s| s.class_unique_id.class_number == own)
+                .map_or(own, Section::primary_class_number);
+            let is_primary = class == own;
+            match by_class.iter_mut().find(|(c, _, _)| *c == class) {
+                Some(entry) if is_primary && !entry.1 => *entry = (class, true, status),
+                Some(_) => {}
+                None => by_class.push((class, is_primary, status)),
+            }
+        }
+        let statuses: Vec<&EnrollmentStatus> = by_class.into_iter().map(|(_, _, s)| s).collect();
+        if statuses.is_empty() {
+            return None;
+        }
+
+        let mut aggregates = 0;
+        let mut combine = |aggregate: fn(&EnrollmentStatus) -> Option<usize>,
+                           own: fn(&EnrollmentStatus) -> usize| {
+            match statuses.iter().find_map(|s| aggregate(s)) {
+                Some(count) => {
+                    aggregates += 1;
+                    count
+                }
+                None => statuses.iter().map(|s| own(s)).sum(),
+            }
+        };
+        let capacity = combine(|s| s.aggregate_capacity, |s| s.capacity);
+        let enrolled = combine(|s| s.aggregate_currently_enrolled, |s| s.currently_enrolled);
+        let waitlist_capacity = combine(|s| s.aggregate_waitlist_capacity, |s| s.waitlist_capacity);
+        let waitlisted = combine(
+            |s| s.aggregate_waitlist_current_size,
+            |s| s.waitlist_current_size,
+        );
+
+        let source = match aggregates {
+            4 => EnrollmentSource::Aggregate,
+            0 => EnrollmentSource::Fallback,
+            _ => EnrollmentSource::Mix,
+        };
+        Some(Self {
+            capacity,
+            enrolled,
+            waitlist_capacity,
+            waitlisted,
+            source,
+        })
+    }
+
+    pub fn open_seats(&self) -> usize {
+        self.capacity.saturating_sub(self.enrolled)
+    }
+}
@@ -0,0 +1,335 @@
+//! Narrowing down the enrollment packages of a course.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{NaiveTime, Weekday};
+use serde_json::{json, Value};
+
+use crate::credits::CreditFilter;
+use crate::error::ParseFilterError;
+use crate::section::schema::{ClassMeeting, CourseSection};
+use crate::section::DeliveryMode;
+
+/// Which ways of taking a course are wanted; a package passes if it's taught in any of the set
+/// ways, and if none are set, every package passes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModeFilter {
+    /// Packages that are online only (including asynchronous ones).
+    pub online: bool,
+    /// Packages without scheduled meetings.
+    pub asynchronous: bool,
+    /// Packages that meet in a classroom, at least partly.
+    pub in_person: bool,
+}
+
+impl ModeFilter {
+    pub fn new(online: bool, asynchronous: bool, in_person: bool) -> Self {
+        Self {
+            online,
+            asynchronous,
+            in_person,
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.online || self.asynchronous || self.in_person
+    }
+
+    pub fn matches(&self, package: &CourseSection) -> bool {
+        let mode = DeliveryMode::of(package);
+        !self.is_set()
+            || (self.online && mode.is_online())
+            || (self.asynchronous && mode == DeliveryMode::Asynchronous)
+            || (self.in_person && !mode.is_online())
+    }
+
+    /// The same filter, as a clause of an `enrollmentPackage` query; `None` if none are set.
+    pub(crate) fn to_query(self) -> Option<Value> {
+        let mut should = Vec::new();
+        if self.online {
+            should.push(json!({ "match": { "onlineOnly": true } }));
+        }
+        if self.asynchronous {
+            should.push(json!({ "match": { "isAsynchronous": true } }));
+        }
+        if self.in_person {
+            should.push(json!({ "match": { "onlineOnly": false } }));
+        }
+
+        match should.len() {
+            0 => None,
+            1 => should.pop(),
+            _ => Some(json!({ "bool": { "should": should, "minimum_should_match": 1 } })),
+        }
+    }
+}
+
+/// A set of days of the week.
+///
+/// Parses from the letters the university uses (`"MWF"`, `"TR"`; `R` is Thursday and `U` is
+/// Sunday) or from day names separated by commas, spaces or slashes (`"tue,thu"`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Days(u8);
+
+impl Days {
+    pub fn contains(&self, day: Weekday) -> bool {
+        self.0 & Self::bit(day) != 0
+    }
+
+    pub fn insert(&mut self, day: Weekday) {
+        self.0 |= Self::bit(day);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_subset(&self, other: &Days) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    pub fn intersects(&self, other: &Days) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// The days in the set, starting on Monday.
+    pub fn iter(&self) -> impl Iterator<Item = Weekday> + '_ {
+        (0..7)
+            .map(Weekday::try_from)
+            .filter_map(Result::ok)
+            .filter(|day| self.contains(*day))
+    }
+
+    fn bit(day: Weekday) -> u8 {
+        1 << day.num_days_from_monday()
+    }
+}
+
+impl FromIterator<Weekday> for Days {
+    fn from_iter<I: IntoIterator<Item = Weekday>>(iter: I) -> Self {
+        let mut days = Days::default();
+        for day in iter {
+            days.insert(day);
+        }
+        days
+    }
+}
+
+impl FromStr for Days {
+    type Err = ParseFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut days = Days::default();
+        for token in s.split([',', ' ', '/']).filter(|t| !t.is_empty()) {
+            if let Some(day) = day_name(token) {
+                days.insert(day);
+                continue;
+            }
+            for letter in token.chars() {
+                days.insert(day_letter(letter).ok_or_else(|| ParseFilterError::new("days", s))?);
+            }
+        }
+
+        if days.is_empty() {
+            return Err(ParseFilterError::new("days", s));
+        }
+        Ok(days)
+    }
+}
+
+/// Writes the days as letters, e.g. `MWF`.
+impl Display for Days {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for day in self.iter() {
+            let letter = match day {
+                Weekday::Mon => 'M',
+                Weekday::Tue => 'T',
+                Weekday::Wed => 'W',
+                Weekday::Thu => 'R',
+                Weekday::Fri => 'F',
+                Weekday::Sat => 'S',
+                Weekday::Sun => 'U',
+            };
+            write!(f, "{}", letter)?;
+        }
+        Ok(())
+    }
+}
+
+fn day_name(name: &str) -> Option<Weekday> {
+    match name.to_lowercase().as_str() {
+        "th" | "thur" | "thurs" => Some(Weekday::Thu),
+        "tu" | "tues" => Some(Weekday::Tue),
+        name if name.len() >= 3 => name.parse().ok(),
+        _ => None,
+    }
+}
+
+fn day_letter(letter: char) -> Option<Weekday> {
+    match letter.to_ascii_uppercase() {
+        'M' => Some(Weekday::Mon),
+        'T' => Some(Weekday::Tue),
+        'W' => Some(Weekday::Wed),
+        'R' => Some(Weekday::Thu),
+        'F' => Some(Weekday::Fri),
+        'S' => Some(Weekday::Sat),
+        'U' => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parses a time of day like `13:20`, `1:20pm` or `11am`.
+pub fn parse_time_of_day(s: &str) -> Result<NaiveTime, ParseFilterError> {
+    let err = || ParseFilterError::new("time of day", s);
+    let lower = s.trim().to_lowercase();
+    let (clock, pm) = match (lower.strip_suffix("am"), lower.strip_suffix("pm")) {
+        (Some(clock), _) => (clock.trim(), Some(false)),
+        (_, Some(clock)) => (clock.trim(), Some(true)),
+        _ => (lower.as_str(), None),
+    };
+
+    let (hour, minute) = clock.split_once(':').unwrap_or((clock, "0"));
+    let mut hour: u32 = hour.parse().map_err(|_| err())?;
+    let minute: u32 = minute.parse().map_err(|_| err())?;
+    match pm {
+        Some(_) if !(1..=12).contains(&hour) => return Err(err()),
+        Some(true) if hour != 12 => hour += 12,
+        Some(false) if hour == 12 => hour = 0,
+        _ => {}
+    }
+
+    NaiveTime::from_hms_opt(hour, minute, 0).ok_or_else(err)
+}
+
+/// When a package's class meetings need to be held; every class meeting has to fit, and a
+/// package without class meetings never passes a set schedule filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScheduleFilter {
+    /// Meetings may only be held on these days.
+    pub days: Option<Days>,
+    /// Meetings may not be held on any of these days.
+    pub not_days: Days,
+    /// Meetings may not start before this time.
+    pub after: Option<NaiveTime>,
+    /// Meetings may not end after this time.
+    pub before: Option<NaiveTime>,
+}
+
+impl ScheduleFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn days(mut self, days: Days) -> Self {
+        self.days = Some(days);
+        self
+    }
+
+    pub fn not_days(mut self, not_days: Days) -> Self {
+        self.not_days = not_days;
+        self
+    }
+
+    pub fn after(mut self, after: NaiveTime) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    pub fn before(mut self, before: NaiveTime) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.days.is_some()
+            || !self.not_days.is_empty()
+            || self.after.is_some()
+            || self.before.is_some()
+    }
+
+    /// Whether a single meeting fits; meetings missing the days or times a bound needs don't.
+    pub fn fits(&self, meeting: &ClassMeeting) -> bool {
+        let days = meeting.days();
+        let on_days = match self.days {
+            Some(allowed) => !days.is_empty() && days.is_subset(&allowed),
+            None => true,
+        };
+        let off_days = !days.intersects(&self.not_days);
+        let after = match self.after {
+            Some(after) => meeting.start_time().map_or(false, |start| start >= after),
+            None => true,
+        };
+        let before = match self.before {
+            Some(before) => meeting.end_time().map_or(false, |end| end <= before),
+            None => true,
+        };
+
+        on_days && off_days && after && before
+    }
+
+    pub fn matches(&self, package: &CourseSection) -> bool {
+        if !self.is_set() {
+            return true;
+        }
+        let mut meetings = package.meetings().peekable();
+        meetings.peek().is_some() && meetings.all(|m| self.fits(m))
+    }
+}
+
+/// Which enrollment packages to keep; by default, every package is kept.
+///
+/// ```
+/// use madpinger::credits::CreditFilter;
+/// use madpinger::section::filter::PackageFilter;
+///
+/// let filter = PackageFilter::new().credits(CreditFilter::new(None, None, Some(3)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageFilter {
+    /// Packages whose credit range can't be read never pass a set credit filter.
+    pub credits: CreditFilter,
+    pub modes: ModeFilter,
+    pub schedule: ScheduleFilter,
+}
+
+impl PackageFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn credits(mut self, credits: CreditFilter) -> Self {
+        self.credits = credits;
+        self
+    }
+
+    pub fn modes(mut self, modes: ModeFilter) -> Self {
+        self.modes = modes;
+        self
+    }
+
+    pub fn schedule(mut self, schedule: ScheduleFilter) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Whether any filter is set at all.
+    pub fn is_set(&self) -> bool {
+        self.credits.is_set() || self.modes.is_set() || self.schedule.is_set()
+    }
+
+    pub fn matches(&self, package: &CourseSection) -> bool {
+        let credits = !self.credits.is_set()
+            || package
+                .credits()
+                .map_or(false, |credits| self.credits.matches(&credits));
+
+        credits && self.modes.matches(package) && self.schedule.matches(package)
+    }
+
+    /// Keeps the packages that pass the filter.
+    pub fn apply(&self, mut packages: Vec<CourseSection>) -> Vec<CourseSection> {
+        packages.retain(|package| self.matches(package));
+        packages
+    }
+}
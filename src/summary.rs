@@ -0,0 +1,64 @@
+//! Seat counts of a course's enrollment packages, as data rather than printed lines.
+
+use chrono::NaiveDate;
+
+use crate::section::cross_listing::{designations, group_cross_listed, CombinedEnrollment};
+use crate::section::schema::{CourseSection, Section, Status};
+use crate::section::DeliveryMode;
+
+/// What's known about an enrollment package, with its listings under other subjects combined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageSummary {
+    /// Every designation the package is listed under, e.g. `["COMP SCI 400", "E C E 400"]`;
+    /// empty if the package has no sections to read them from.
+    pub designations: Vec<String>,
+    /// The package's components, e.g. `["LEC 001", "DIS 312"]`.
+    pub components: Vec<String>,
+    /// The topic of a special topics package.
+    pub topic: Option<String>,
+    pub mode: DeliveryMode,
+    pub status: Status,
+    /// Seat counts across every listing; `None` if the API gave none.
+    pub enrollment: Option<CombinedEnrollment>,
+    /// When and where the package meets, e.g. `MWF 9:55–10:45 @ Van Vleck Hall B102`.
+    pub meetings: Vec<String>,
+    pub first_day: Option<NaiveDate>,
+    pub last_day: Option<NaiveDate>,
+}
+
+impl PackageSummary {
+    /// The designations joined together, e.g. `COMP SCI 400 / E C E 400`.
+    pub fn designation(&self) -> String {
+        self.designations.join(" / ")
+    }
+
+    pub fn open_seats(&self) -> Option<usize> {
+        self.enrollment.map(|e| e.open_seats())
+    }
+}
+
+/// Summarizes a course's packages, one summary per package (cross-listed packages count once).
+pub fn summarize_packages(packages: &[CourseSection]) -> Vec<PackageSummary> {
+    group_cross_listed(packages)
+        .into_iter()
+        .filter_map(|listings| {
+            let package = *listings.first()?;
+            let sections = &package.sections;
+            Some(PackageSummary {
+                designations: designations(&listings),
+                components: sections.iter().map(Section::component).collect(),
+                topic: package.topic().map(|t| t.short_description.clone()),
+                mode: DeliveryMode::of(package),
+                status: package.package_enrollment_status.status.clone(),
+                enrollment: CombinedEnrollment::of(&listings),
+                meetings: package
+                    .class_meetings
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                first_day: sections.iter().filter_map(Section::first_day).min(),
+                last_day: sections.iter().filter_map(Section::last_day).max(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,27 @@
+//! Reading the API's timestamps, which are milliseconds since the Unix epoch, in campus time.
+//!
+//! Meeting times are stored as a time of day on 1970-01-01, so they're read with that day's
+//! offset; dates (exams, section start and end dates) are read with the offset of their own day.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+
+/// The time zone UW–Madison (and so the API) keeps time in.
+pub const CAMPUS_TZ: Tz = chrono_tz::America::Chicago;
+
+/// A timestamp as a date and time on campus.
+pub fn campus_datetime(millis: u64) -> Option<DateTime<Tz>> {
+    CAMPUS_TZ
+        .timestamp_millis_opt(i64::try_from(millis).ok()?)
+        .single()
+}
+
+/// The time of day a timestamp falls on, on campus.
+pub fn campus_time(millis: u64) -> Option<NaiveTime> {
+    campus_datetime(millis).map(|dt| dt.time())
+}
+
+/// The day a timestamp falls on, on campus.
+pub fn campus_date(millis: u64) -> Option<NaiveDate> {
+    campus_datetime(millis).map(|dt| dt.date_naive())
+}
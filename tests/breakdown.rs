@@ -0,0 +1,38 @@
+use madpinger::section::breakdown::Bottleneck;
+
+use common::packages;
+
+mod common;
+
+#[test]
+fn the_fullest_component_is_the_bottleneck() {
+    let packages = packages();
+
+    let closed = Bottleneck::of(&packages[1]).unwrap();
+    assert_eq!(closed.section.component(), "DIS 312");
+    assert!(closed.is_full());
+    assert_eq!(
+        closed.to_string(),
+        "lecture has 40 open seats but DIS 312 is full"
+    );
+
+    let open = Bottleneck::of(&packages[0]).unwrap();
+    assert!(!open.is_full());
+    assert_eq!(
+        open.to_string(),
+        "lecture has 40 open seats but DIS 311 only has 8"
+    );
+}
+
+#[test]
+fn lone_components_are_their_own_bottleneck() {
+    let mut package = packages().remove(0);
+    package.sections.truncate(1);
+    assert_eq!(
+        Bottleneck::of(&package).unwrap().to_string(),
+        "LEC 001 has 40 open seats"
+    );
+
+    package.sections.clear();
+    assert!(Bottleneck::of(&package).is_none());
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use madpinger::section::CourseKey;
+use madpinger::{CseClient, MadpingerError, RetryPolicy};
+
+use common::{fixture, MockResponse, MockServer};
+
+mod common;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn bulk_fetch_keeps_per_course_errors() -> Result<(), Box<dyn Error>> {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let (in_flight2, peak2) = (in_flight.clone(), peak.clone());
+
+    let server = MockServer::start(move |req| {
+        let now = in_flight2.fetch_add(1, Ordering::SeqCst) + 1;
+        peak2.fetch_max(now, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(20));
+        in_flight2.fetch_sub(1, Ordering::SeqCst);
+
+        if req.path.ends_with("/missing") {
+            MockResponse::status(404)
+        } else {
+            MockResponse::ok(fixture("sections.json"))
+        }
+    })
+    .await;
+    let client = CseClient::builder()
+        .base_url(server.url())
+        .retry_policy(RetryPolicy::none())
+        .build()?;
+
+    let keys = vec![
+        ("1242", "266", "024798"),
+        ("1242", "266", "missing"),
+        ("1242", "600", "000001"),
+        ("1242", "600", "000002"),
+        ("1242", "600", "000003"),
+    ];
+    let results: HashMap<CourseKey, Result<_, MadpingerError>> =
+        client.sections_bulk(keys, 2).collect().await;
+
+    assert_eq!(results.len(), 5);
+    assert!(results[&("1242", "266", "missing").into()].is_err());
+    assert_eq!(
+        results[&("1242", "266", "024798").into()]
+            .as_ref()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(peak.load(Ordering::SeqCst) <= 2);
+    Ok(())
+}
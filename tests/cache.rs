@@ -0,0 +1,67 @@
+use std::error::Error;
+use std::time::Duration;
+
+use madpinger::cache::{CacheStats, ResponseCache};
+use madpinger::client::Endpoint;
+use madpinger::CseClient;
+
+use common::{fixture, MockResponse, MockServer};
+
+mod common;
+
+async fn fetch_twice(cache: ResponseCache) -> Result<(usize, CacheStats), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("sections.json"))).await;
+    let client = CseClient::builder()
+        .base_url(server.url())
+        .cache(cache)
+        .build()?;
+
+    client.sections("1242", "266", "024798").await?;
+    let course_sections = client.sections("1242", "266", "024798").await?;
+    assert_eq!(course_sections.len(), 2);
+
+    Ok((server.hits(), client.cache().unwrap().stats()))
+}
+
+#[tokio::test]
+async fn fresh_responses_come_from_the_cache() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+
+    let (hits, stats) = fetch_twice(ResponseCache::new(dir.path())).await?;
+
+    assert_eq!(hits, 1);
+    assert_eq!(
+        stats,
+        CacheStats {
+            hits: 1,
+            misses: 1,
+            expired: 0,
+            writes: 1
+        }
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn expired_responses_are_refetched() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let cache = ResponseCache::new(dir.path()).ttl(Endpoint::Sections, Duration::ZERO);
+
+    let (hits, stats) = fetch_twice(cache).await?;
+
+    assert_eq!(hits, 2);
+    assert_eq!(stats.expired, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn refreshing_skips_reads_but_writes() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+
+    let (hits, stats) = fetch_twice(ResponseCache::new(dir.path()).refresh(true)).await?;
+
+    assert_eq!(hits, 2);
+    assert_eq!(stats.writes, 2);
+    assert_eq!(stats.hits, 0);
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use madpinger::cache::ResponseCache;
+use madpinger::cassette::Cassette;
+use madpinger::client::{ApiRequest, Endpoint};
+use madpinger::{CseClient, MadpingerError};
+
+use common::{fixture, MockResponse, MockServer};
+
+mod common;
+
+#[tokio::test]
+async fn recorded_responses_replay_without_network() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("sections.json"))).await;
+    let dir = tempfile::tempdir()?;
+
+    let recorder = CseClient::builder()
+        .base_url(server.url())
+        .cassette(Cassette::record(dir.path()))
+        .build()?;
+    let recorded = recorder.sections("1242", "266", "024798").await?;
+
+    let player = CseClient::builder()
+        .base_url(server.url())
+        .cassette(Cassette::replay(dir.path()))
+        .build()?;
+    let replayed = player.sections("1242", "266", "024798").await?;
+
+    assert_eq!(server.hits(), 1);
+    assert_eq!(recorded.len(), replayed.len());
+
+    let request = ApiRequest::get(
+        Endpoint::Sections,
+        player.sections_url("1242", "266", "024798"),
+    );
+    let cassette = player.cassette().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(cassette.path_for(&request))?,
+        fixture("sections.json")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn cassettes_replay_against_another_base_url() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("sections.json"))).await;
+    let dir = tempfile::tempdir()?;
+
+    let recorder = CseClient::builder()
+        .base_url(server.url())
+        .cassette(Cassette::record(dir.path()))
+        .build()?;
+    recorder.sections("1242", "266", "024798").await?;
+
+    let player = CseClient::builder()
+        .base_url("http://proxy.invalid:8080")
+        .cassette(Cassette::replay(dir.path()))
+        .build()?;
+    let replayed = player.sections("1242", "266", "024798").await?;
+
+    assert_eq!(server.hits(), 1);
+    assert_eq!(replayed.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn cached_responses_are_recorded() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("sections.json"))).await;
+    let cache_dir = tempfile::tempdir()?;
+    let dir = tempfile::tempdir()?;
+
+    let warmer = CseClient::builder()
+        .base_url(server.url())
+        .cache(ResponseCache::new(cache_dir.path()))
+        .build()?;
+    warmer.sections("1242", "266", "024798").await?;
+
+    let recorder = CseClient::builder()
+        .base_url(server.url())
+        .cache(ResponseCache::new(cache_dir.path()))
+        .cassette(Cassette::record(dir.path()))
+        .build()?;
+    recorder.sections("1242", "266", "024798").await?;
+    assert_eq!(server.hits(), 1);
+
+    let player = CseClient::builder()
+        .base_url(server.url())
+        .cassette(Cassette::replay(dir.path()))
+        .build()?;
+    let replayed = player.sections("1242", "266", "024798").await?;
+    assert_eq!(replayed.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn replaying_an_unrecorded_request_fails() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let player = CseClient::builder()
+        .cassette(Cassette::replay(dir.path()))
+        .build()?;
+
+    let err = player.sections("1242", "266", "000000").await.unwrap_err();
+
+    assert!(
+        matches!(err, MadpingerError::CassetteMiss { .. }),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+/// Replays the payloads committed under `tests/cassettes`.
+#[tokio::test]
+async fn committed_cassette_deserializes() -> Result<(), Box<dyn Error>> {
+    let dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "cassettes"]
+        .iter()
+        .collect();
+    let player = CseClient::builder()
+        .cassette(Cassette::replay(dir))
+        .build()?;
+
+    let course_sections = player.sections("1242", "266", "024798").await?;
+
+    assert_eq!(course_sections.len(), 2);
+    Ok(())
+}
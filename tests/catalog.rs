@@ -0,0 +1,231 @@
+use std::error::Error;
+
+use madpinger::catalog::{self, Catalog, CatalogEntry, CatalogIndex};
+use madpinger::credits::CreditFilter;
+use madpinger::search::query::{SearchQuery, SortOrder};
+use madpinger::search::schema::SearchedCourse;
+use madpinger::section::filter::{ModeFilter, PackageFilter, ScheduleFilter};
+use madpinger::{CourseStatusFilters, CseClient};
+use serde_json::json;
+
+use common::{course, fixture, hit, packages, MockResponse, MockServer};
+
+mod common;
+
+fn catalog_course(
+    course_id: &str,
+    catalog_number: &str,
+    title: &str,
+    description: &str,
+) -> SearchedCourse {
+    let mut hit = hit();
+    let designation = format!("COMP SCI {}", catalog_number);
+    hit["courseId"] = json!(course_id);
+    hit["catalogNumber"] = json!(catalog_number);
+    hit["catalogSort"] = json!(format!("{:0>5}", catalog_number));
+    hit["courseDesignation"] = json!(designation);
+    hit["fullCourseDesignation"] = json!(format!("COMPUTER SCIENCES {}", catalog_number));
+    hit["title"] = json!(title);
+    hit["description"] = json!(description);
+    course(hit)
+}
+
+fn catalog() -> Catalog {
+    let mut databases = catalog_course(
+        "024799",
+        "564",
+        "Database Management Systems",
+        "Storing data.",
+    );
+    databases.credit_range = "4".to_string();
+    databases.minimum_credits = 4;
+    databases.maximum_credits = 4;
+
+    let entries = vec![
+        CatalogEntry {
+            course: catalog_course(
+                "024798",
+                "400",
+                "Programming III",
+                "Algorithms on data structures.",
+            ),
+            instructors: vec!["Hobbes Legault".to_string()],
+            packages: None,
+        },
+        CatalogEntry {
+            course: catalog_course(
+                "024800",
+                "577",
+                "Introduction to Algorithms",
+                "Designing algorithms.",
+            ),
+            instructors: vec![],
+            packages: None,
+        },
+        CatalogEntry {
+            course: databases,
+            instructors: vec![],
+            packages: None,
+        },
+    ];
+    Catalog::new("1242", entries)
+}
+
+/// The courses matching a query string, with no package filters.
+fn search<'a>(index: &CatalogIndex<'a>, query: &str) -> Vec<&'a SearchedCourse> {
+    index.search(
+        &SearchQuery::new("1242").query(query),
+        &PackageFilter::new(),
+    )
+}
+
+fn designations(courses: &[&SearchedCourse]) -> Vec<String> {
+    courses
+        .iter()
+        .map(|c| c.course_designation.clone())
+        .collect()
+}
+
+#[test]
+fn catalogs_round_trip_through_disk() -> Result<(), Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("catalogs").join("catalog-1242.json");
+    let saved = catalog();
+    saved.save(&path)?;
+
+    let loaded = Catalog::load(&path)?;
+    assert_eq!(loaded.term_code, "1242");
+    assert_eq!(loaded.entries.len(), saved.entries.len());
+    assert_eq!(loaded.entries[0].instructors, ["Hobbes Legault"]);
+    assert_eq!(
+        loaded.entries[2].course.title,
+        "Database Management Systems"
+    );
+    Ok(())
+}
+
+#[test]
+fn titles_outrank_descriptions() {
+    let catalog = catalog();
+    let index = catalog.index();
+
+    let hits = search(&index, "algorithms");
+    assert_eq!(designations(&hits), ["COMP SCI 577", "COMP SCI 400"]);
+
+    // every term has to match
+    let hits = search(&index, "designing algorithms");
+    assert_eq!(designations(&hits), ["COMP SCI 577"]);
+}
+
+#[test]
+fn terms_match_designations_instructors_and_prefixes() {
+    let catalog = catalog();
+    let index = catalog.index();
+
+    let hits = search(&index, "comp sci 564");
+    assert_eq!(designations(&hits), ["COMP SCI 564"]);
+
+    let hits = search(&index, "legault");
+    assert_eq!(designations(&hits), ["COMP SCI 400"]);
+
+    let hits = search(&index, "prog");
+    assert_eq!(designations(&hits), ["COMP SCI 400"]);
+}
+
+#[test]
+fn offline_search_applies_filters_sorting_and_pages() {
+    let catalog = catalog();
+    let index = catalog.index();
+
+    let query = SearchQuery::new("1242")
+        .query("*")
+        .credits(CreditFilter::new(Some(4), None, None));
+    assert_eq!(
+        designations(&index.search(&query, &PackageFilter::new())),
+        ["COMP SCI 564"]
+    );
+
+    let query = SearchQuery::new("1242")
+        .query("*")
+        .sort_order(SortOrder::CatalogNumber)
+        .page_size(2)
+        .page(2);
+    let page = index.search_page(&query, &PackageFilter::new());
+    assert_eq!(page.found, 3);
+    let hits: Vec<&str> = page
+        .hits
+        .iter()
+        .map(|c| c.course_designation.as_str())
+        .collect();
+    assert_eq!(hits, ["COMP SCI 577"]);
+}
+
+#[test]
+fn offline_search_checks_packages() -> Result<(), Box<dyn Error>> {
+    let mut catalog = catalog();
+    // COMP SCI 400 has an open package and a waitlisted one, the other two weren't downloaded
+    catalog.entries[0].packages = Some(packages());
+    catalog.entries[2].packages = Some(vec![]);
+    let index = catalog.index();
+    let everything = SearchQuery::new("1242");
+    let any_package = PackageFilter::new();
+
+    let found = |query: &SearchQuery, packages: &PackageFilter| {
+        designations(&index.search(query, packages))
+    };
+    assert_eq!(found(&everything, &any_package), ["COMP SCI 400"]);
+
+    let open = everything
+        .clone()
+        .statuses(CourseStatusFilters::new(true, false, false));
+    assert_eq!(found(&open, &any_package), ["COMP SCI 400"]);
+    let closed = everything
+        .clone()
+        .statuses(CourseStatusFilters::new(false, false, true));
+    assert!(found(&closed, &any_package).is_empty());
+    let online = everything
+        .clone()
+        .modes(ModeFilter::new(true, false, false));
+    assert!(found(&online, &any_package).is_empty());
+
+    // the package without Thursday meetings is the open one
+    let thursdays_off = PackageFilter::new().schedule(ScheduleFilter::new().not_days("R".parse()?));
+    assert_eq!(found(&open, &thursdays_off), ["COMP SCI 400"]);
+    let waitlisted = everything.statuses(CourseStatusFilters::new(false, true, false));
+    assert!(found(&waitlisted, &thursdays_off).is_empty());
+    Ok(())
+}
+
+#[test]
+fn package_filters_are_ignored_without_packages() {
+    let catalog = catalog();
+    let index = catalog.index();
+
+    let closed = SearchQuery::new("1242").statuses(CourseStatusFilters::new(false, false, true));
+    assert_eq!(index.search(&closed, &PackageFilter::new()).len(), 3);
+}
+
+#[tokio::test]
+async fn courses_whose_packages_fail_are_saved_without_them() -> Result<(), Box<dyn Error>> {
+    let mut broken = hit();
+    broken["courseId"] = json!("999999");
+    let body = json!({ "found": 2, "hits": [hit(), broken] });
+    let server = MockServer::start(move |req| {
+        if req.path.ends_with("/999999") {
+            MockResponse::status(404)
+        } else if req.method == "POST" {
+            MockResponse::ok(body.to_string())
+        } else {
+            MockResponse::ok(fixture("sections.json"))
+        }
+    })
+    .await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let catalog = catalog::download(&client, "1242", true, 4).await?;
+    assert_eq!(catalog.entries.len(), 2);
+    assert_eq!(catalog.entries[0].packages.as_ref().map(Vec::len), Some(2));
+    assert_eq!(catalog.entries[0].instructors, ["Ada Lovelace"]);
+    assert!(catalog.entries[1].packages.is_none());
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use madpinger::client::{ApiRequest, Endpoint};
+use madpinger::{CourseStatusFilters, CseClient};
+
+use common::{fixture, MockResponse, MockServer};
+
+mod common;
+
+#[tokio::test]
+async fn sections_resolve_against_base_url() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("sections.json"))).await;
+    let client = CseClient::builder()
+        .base_url(format!("{}/", server.url()))
+        .build()?;
+
+    let course_sections = client.sections("1242", "266", "024798").await?;
+
+    assert_eq!(course_sections.len(), 2);
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(
+        requests[0].path,
+        "/api/search/v1/enrollmentPackages/1242/266/024798"
+    );
+    assert!(requests[0].header("user-agent").is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_posts_to_base_url() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("search.json"))).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let api_ping = client
+        .search("1242", "programming", 10, CourseStatusFilters::default())
+        .await?;
+
+    assert_eq!(api_ping.found(), 1);
+    assert_eq!(api_ping.hits()[0].course_designation, "COMP SCI 400");
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/api/search/v1");
+    assert!(requests[0].body.contains("\"selectedTerm\""));
+    Ok(())
+}
+
+#[tokio::test]
+async fn observers_see_raw_bodies() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("search.json"))).await;
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    let client = CseClient::builder()
+        .base_url(server.url())
+        .observer(move |req: &ApiRequest, body: &str| {
+            seen2.lock().unwrap().push((req.endpoint, body.to_string()))
+        })
+        .build()?;
+
+    client
+        .search("1242", "programming", 10, CourseStatusFilters::default())
+        .await?;
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(*seen, vec![(Endpoint::Search, fixture("search.json"))]);
+    Ok(())
+}
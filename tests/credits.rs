@@ -0,0 +1,52 @@
+use std::error::Error;
+
+use madpinger::credits::{CreditFilter, CreditRange};
+use madpinger::search::query::SearchQuery;
+use madpinger::section::filter::PackageFilter;
+use serde_json::json;
+
+use common::packages;
+
+mod common;
+
+#[test]
+fn credit_ranges_parse() {
+    assert_eq!("3".parse(), Ok(CreditRange::exactly(3)));
+    assert_eq!("1-3".parse(), Ok(CreditRange::new(1, 3)));
+    assert_eq!(" 1 - 6 ".parse(), Ok(CreditRange::new(1, 6)));
+    assert!("".parse::<CreditRange>().is_err());
+    assert!("three".parse::<CreditRange>().is_err());
+    assert_eq!(CreditRange::new(1, 3).to_string(), "1-3");
+}
+
+#[test]
+fn variable_credit_courses_pass_if_some_credit_count_fits() {
+    let variable = CreditRange::new(1, 6);
+
+    assert!(CreditFilter::new(None, Some(3), None).matches(&variable));
+    assert!(CreditFilter::new(Some(4), None, None).matches(&variable));
+    assert!(CreditFilter::new(None, None, Some(6)).matches(&variable));
+    assert!(!CreditFilter::new(Some(7), None, None).matches(&variable));
+    assert!(!CreditFilter::new(Some(2), Some(4), None).matches(&CreditRange::exactly(5)));
+}
+
+#[test]
+fn credit_bounds_are_sent_to_the_api() -> Result<(), Box<dyn Error>> {
+    let query = SearchQuery::new("1242").credits(CreditFilter::new(Some(2), Some(4), None));
+
+    let payload = serde_json::to_value(&query)?;
+    let filters = payload["filters"].as_array().unwrap();
+    assert!(filters.contains(&json!({ "range": { "maximumCredits": { "gte": 2 } } })));
+    assert!(filters.contains(&json!({ "range": { "minimumCredits": { "lte": 4 } } })));
+    Ok(())
+}
+
+#[test]
+fn packages_are_filtered_by_credits() {
+    assert_eq!(packages()[0].credits(), Some(CreditRange::exactly(3)));
+
+    let three = PackageFilter::new().credits(CreditFilter::new(None, None, Some(3)));
+    let four = PackageFilter::new().credits(CreditFilter::new(Some(4), None, None));
+    assert_eq!(three.apply(packages()).len(), 2);
+    assert!(four.apply(packages()).is_empty());
+}
@@ -0,0 +1,115 @@
+use std::error::Error;
+
+use madpinger::search::merge_cross_listed;
+use madpinger::search::schema::SearchedCourse;
+use madpinger::section::cross_listing::{
+    designations, group_cross_listed, CombinedEnrollment, EnrollmentSource,
+};
+use madpinger::section::schema::CourseSection;
+use serde_json::{json, Value};
+
+use common::{course, hit, package_values, packages_from};
+
+mod common;
+
+/// The packages of `sections.json`, plus an `E C E 400` listing of each of them reporting the
+/// same seats (or, with `own_seats`, seats of its own).
+fn listed_packages(aggregates: bool, own_seats: bool) -> Vec<CourseSection> {
+    let mut packages = package_values();
+    let mut listings = packages.clone();
+    for (i, listing) in listings.iter_mut().enumerate() {
+        let status = &mut listing["enrollmentStatus"];
+        let class_number = status["classUniqueId"]["classNumber"].as_u64().unwrap();
+        status["classUniqueId"]["classNumber"] = json!(class_number + 100);
+        if own_seats {
+            status["capacity"] = json!(10);
+            status["currentlyEnrolled"] = json!(4 + i);
+        }
+        for section in listing["sections"].as_array_mut().unwrap() {
+            let class_number = section["classUniqueId"]["classNumber"].as_u64().unwrap();
+            section["classUniqueId"]["classNumber"] = json!(class_number + 100);
+            section["subject"]["subjectCode"] = json!("600");
+            section["subject"]["shortDescription"] = json!("E C E");
+            section["crossListing"] = json!({
+                "crossListedType": "SAME_CLASS",
+                "primaryClassNumber": class_number,
+                "primarySubject": null
+            });
+        }
+    }
+    packages.extend(listings);
+
+    if !aggregates {
+        for package in &mut packages {
+            let status = &mut package["enrollmentStatus"];
+            for field in [
+                "aggregateCapacity",
+                "aggregateCurrentlyEnrolled",
+                "aggregateWaitlistCapacity",
+                "aggregateWaitlistCurrentSize",
+            ] {
+                status[field] = Value::Null;
+            }
+        }
+    }
+    packages_from(packages)
+}
+
+fn searched(subject_code: &str, short_description: &str) -> SearchedCourse {
+    let mut hit = hit();
+    hit["subject"]["subjectCode"] = json!(subject_code);
+    hit["subject"]["shortDescription"] = json!(short_description);
+    hit["courseDesignation"] = json!(format!("{} 400", short_description));
+    course(hit)
+}
+
+#[test]
+fn cross_listed_hits_are_merged() {
+    let hits = vec![searched("266", "COMP SCI"), searched("600", "E C E")];
+
+    let merged = merge_cross_listed(hits);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].course_designation, "COMP SCI 400");
+    assert_eq!(merged[0].cross_listed_designations(), ["E C E 400"]);
+}
+
+#[test]
+fn listings_of_a_package_are_grouped() {
+    let packages = listed_packages(false, false);
+
+    let groups = group_cross_listed(&packages);
+    assert_eq!(groups.len(), 2);
+    assert!(groups.iter().all(|listings| listings.len() == 2));
+    assert_eq!(designations(&groups[0]), ["COMP SCI 400", "E C E 400"]);
+}
+
+#[test]
+fn listings_of_the_same_seats_count_once() -> Result<(), Box<dyn Error>> {
+    let packages = listed_packages(false, false);
+    let groups = group_cross_listed(&packages);
+    let combined = CombinedEnrollment::of(&groups[1]).ok_or("no enrollment")?;
+    assert_eq!(combined.source, EnrollmentSource::Fallback);
+    assert_eq!((combined.enrolled, combined.capacity), (30, 30));
+    assert_eq!((combined.waitlisted, combined.waitlist_capacity), (3, 5));
+    assert_eq!(combined.open_seats(), 0);
+    Ok(())
+}
+
+#[test]
+fn the_primary_listing_is_counted() -> Result<(), Box<dyn Error>> {
+    // even when another listing comes first
+    let mut packages = listed_packages(false, true);
+    packages.reverse();
+    let groups = group_cross_listed(&packages);
+    assert_eq!(designations(&groups[0]), ["E C E 400", "COMP SCI 400"]);
+    let combined = CombinedEnrollment::of(&groups[0]).ok_or("no enrollment")?;
+    assert_eq!((combined.enrolled, combined.capacity), (30, 30));
+
+    // aggregates already cover every listing, so they aren't added up again
+    let packages = listed_packages(true, true);
+    let groups = group_cross_listed(&packages);
+    let combined = CombinedEnrollment::of(&groups[0]).ok_or("no enrollment")?;
+    assert_eq!(combined.source, EnrollmentSource::Aggregate);
+    assert_eq!((combined.enrolled, combined.capacity), (22, 30));
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+
+use madpinger::{CseClient, MadpingerError};
+use serde_json::Value;
+
+use common::{package_values, MockResponse, MockServer};
+
+mod common;
+
+#[tokio::test]
+async fn json_errors_report_the_offending_path() -> Result<(), Box<dyn Error>> {
+    let mut packages = package_values();
+    packages[1]["sections"][0]["instructionMode"] = Value::from(5);
+    let body = Value::from(packages).to_string();
+
+    let server = MockServer::start(move |_| MockResponse::ok(body.clone())).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    match client.sections("1242", "266", "024798").await {
+        Err(MadpingerError::Json { path, snippet, .. }) => {
+            assert_eq!(path, "[1].sections[0].instructionMode");
+            assert_eq!(snippet, "5");
+        }
+        other => panic!("expected a JSON error, got {other:?}"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn malformed_bodies_are_json_errors() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok("[{\"id\": oops}]")).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let err = client.sections("1242", "266", "024798").await.unwrap_err();
+    assert!(matches!(err, MadpingerError::Json { .. }), "{err:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn unsuccessful_statuses_are_status_errors() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::status(404)).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    match client.sections("1242", "266", "024798").await {
+        Err(MadpingerError::Status { status, .. }) => assert_eq!(status.as_u16(), 404),
+        other => panic!("expected a status error, got {other:?}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+
+use chrono::{NaiveDate, NaiveTime};
+use madpinger::section::schema::ClassMeeting;
+use serde_json::Value;
+
+use common::{package_values, packages};
+
+mod common;
+
+#[test]
+fn timestamps_are_read_in_campus_time() -> Result<(), Box<dyn Error>> {
+    let package = &packages()[0];
+    let lecture = &package.class_meetings[0];
+    assert_eq!(lecture.start_time(), NaiveTime::from_hms_opt(9, 55, 0));
+    assert_eq!(lecture.end_time(), NaiveTime::from_hms_opt(10, 45, 0));
+
+    let exam = &package.class_meetings[2];
+    assert_eq!(exam.exam_day(), NaiveDate::from_ymd_opt(2023, 12, 17));
+
+    let section = &package.sections[0];
+    assert_eq!(section.first_day(), NaiveDate::from_ymd_opt(2023, 9, 6));
+    assert_eq!(section.last_day(), NaiveDate::from_ymd_opt(2023, 12, 13));
+    Ok(())
+}
+
+#[test]
+fn meetings_are_formatted_for_people() -> Result<(), Box<dyn Error>> {
+    let package = &packages()[0];
+    let formatted: Vec<String> = package
+        .class_meetings
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        formatted,
+        [
+            "MWF 9:55–10:45 @ Van Vleck Hall B102",
+            "T 11:00–11:50 @ Van Vleck Hall B215",
+            "exam Dec 17 19:45–21:45 @ Van Vleck Hall 1800",
+        ]
+    );
+
+    let mut packages = package_values();
+    let meeting = &mut packages[0]["classMeetings"][0];
+    for field in ["meetingTimeStart", "meetingTimeEnd", "building", "room"] {
+        meeting[field] = Value::Null;
+    }
+    meeting["meetingDaysList"] = Value::Array(vec![]);
+    let meeting: ClassMeeting = serde_json::from_value(meeting.take())?;
+    assert_eq!(meeting.to_string(), "TBA");
+    Ok(())
+}
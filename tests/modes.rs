@@ -0,0 +1,57 @@
+use std::error::Error;
+
+use madpinger::search::query::SearchQuery;
+use madpinger::section::filter::{ModeFilter, PackageFilter};
+use madpinger::section::schema::CourseSection;
+use madpinger::section::DeliveryMode;
+use serde_json::json;
+
+use common::{package_values, packages_from};
+
+mod common;
+
+/// The fixture packages: the first in a classroom, the second made online and asynchronous.
+fn packages() -> Vec<CourseSection> {
+    let mut packages = package_values();
+    packages[1]["onlineOnly"] = json!(true);
+    packages[1]["isAsynchronous"] = json!(true);
+    packages[1]["modesOfInstruction"] = json!(["Online Only"]);
+    packages_from(packages)
+}
+
+#[test]
+fn delivery_modes_are_derived_from_packages() {
+    let packages = packages();
+    assert_eq!(DeliveryMode::of(&packages[0]), DeliveryMode::InPerson);
+    assert_eq!(DeliveryMode::of(&packages[1]), DeliveryMode::Asynchronous);
+    assert_eq!(DeliveryMode::Asynchronous.to_string(), "online, async");
+}
+
+#[test]
+fn packages_are_filtered_by_mode() {
+    let ids = |modes: ModeFilter| -> Vec<usize> {
+        PackageFilter::new()
+            .modes(modes)
+            .apply(packages())
+            .iter()
+            .map(|p| p.enrollment_class_number)
+            .collect()
+    };
+
+    assert_eq!(ids(ModeFilter::default()).len(), 2);
+    assert_eq!(ids(ModeFilter::new(false, false, true)), [30002]);
+    assert_eq!(ids(ModeFilter::new(true, false, false)), [30004]);
+    assert_eq!(ids(ModeFilter::new(false, true, true)).len(), 2);
+}
+
+#[test]
+fn modes_are_sent_in_the_package_query() -> Result<(), Box<dyn Error>> {
+    let query = SearchQuery::new("1242").modes(ModeFilter::new(true, false, false));
+
+    let payload = serde_json::to_value(&query)?;
+    let must = payload["filters"][0]["has_child"]["query"]["bool"]["must"]
+        .as_array()
+        .unwrap();
+    assert!(must.contains(&json!({ "match": { "onlineOnly": true } })));
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+
+use madpinger::search::offered::{Cadence, Season, TypicallyOffered};
+use madpinger::search::query::SearchQuery;
+
+use common::{course, hit};
+
+mod common;
+
+#[test]
+fn typically_offered_is_parsed() {
+    assert_eq!(
+        TypicallyOffered::parse("Fall, Spring, Summer"),
+        TypicallyOffered::Seasons {
+            seasons: vec![Season::Fall, Season::Spring, Season::Summer],
+            cadence: Cadence::EveryYear
+        }
+    );
+    assert_eq!(
+        TypicallyOffered::parse("Spring (odd years)"),
+        TypicallyOffered::Seasons {
+            seasons: vec![Season::Spring],
+            cadence: Cadence::OddYears
+        }
+    );
+    assert_eq!(
+        TypicallyOffered::parse("Occasionally"),
+        TypicallyOffered::Occasionally
+    );
+    assert_eq!(
+        TypicallyOffered::parse("Contact the department"),
+        TypicallyOffered::Unknown("Contact the department".to_string())
+    );
+    assert_eq!(
+        TypicallyOffered::parse("spring, fall").to_string(),
+        "Fall, Spring"
+    );
+}
+
+#[test]
+fn courses_are_filtered_by_season() -> Result<(), Box<dyn Error>> {
+    let mut course = course(hit());
+    assert!(course.offered().includes(Season::Summer));
+
+    let fall = SearchQuery::new("1242").offered("fall".parse()?);
+    assert!(fall.matches(&course));
+    course.typically_offered = "Spring".to_string();
+    assert!(!fall.matches(&course));
+    course.typically_offered = "Occasionally".to_string();
+    assert!(!fall.matches(&course));
+    Ok(())
+}
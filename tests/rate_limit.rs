@@ -0,0 +1,64 @@
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+use madpinger::{CseClient, RateLimiter};
+use tokio::time::Instant;
+
+use common::{fixture, MockResponse, MockServer};
+
+mod common;
+
+#[tokio::test(start_paused = true)]
+async fn limiter_spaces_out_requests() {
+    let limiter = RateLimiter::new(2.0, 1);
+    let start = Instant::now();
+
+    for _ in 0..5 {
+        limiter.acquire().await;
+    }
+
+    assert_eq!(start.elapsed(), Duration::from_secs(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn limiter_is_shared_across_tasks() {
+    let limiter = Arc::new(RateLimiter::new(4.0, 2));
+    let start = Instant::now();
+
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire().await })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    // the first two requests are a burst, the other eight take a quarter second each
+    assert_eq!(start.elapsed(), Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn client_clones_share_the_limiter() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("sections.json"))).await;
+    let client = CseClient::builder()
+        .base_url(server.url())
+        .rate_limit(20.0)
+        .build()?;
+    let start = Instant::now();
+
+    let (a, b) = (client.clone(), client.clone());
+    let (ra, rb) = tokio::join!(
+        async move { a.sections("1242", "266", "024798").await },
+        async move { b.sections("1242", "266", "024798").await },
+    );
+    ra?;
+    rb?;
+    client.sections("1242", "266", "024798").await?;
+
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(server.hits(), 3);
+    Ok(())
+}
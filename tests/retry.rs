@@ -0,0 +1,72 @@
+use std::error::Error;
+use std::time::Duration;
+
+use madpinger::{CseClient, MadpingerError, RetryPolicy};
+
+use common::{fixture, MockResponse, MockServer};
+
+mod common;
+
+fn client(server: &MockServer, max_attempts: u32) -> Result<CseClient, Box<dyn Error>> {
+    Ok(CseClient::builder()
+        .base_url(server.url())
+        .retry_policy(RetryPolicy::new(max_attempts, Duration::from_millis(1)))
+        .build()?)
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::scripted(vec![
+        MockResponse::status(503),
+        MockResponse::status(429).header("Retry-After", "0"),
+        MockResponse::status(502),
+        MockResponse::ok(fixture("sections.json")),
+    ])
+    .await;
+
+    let course_sections = client(&server, 4)?
+        .sections("1242", "266", "024798")
+        .await?;
+
+    assert_eq!(course_sections.len(), 2);
+    assert_eq!(server.hits(), 4);
+    Ok(())
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::scripted(vec![MockResponse::status(503)]).await;
+
+    let err = client(&server, 3)?
+        .sections("1242", "266", "024798")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, MadpingerError::Status { .. }), "{err:?}");
+    assert_eq!(server.hits(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn permanent_failures_are_not_retried() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::scripted(vec![MockResponse::status(404)]).await;
+
+    let _ = client(&server, 3)?.sections("1242", "266", "024798").await;
+
+    assert_eq!(server.hits(), 1);
+    Ok(())
+}
+
+#[test]
+fn backoff_doubles_up_to_max_delay() {
+    let policy = RetryPolicy {
+        max_attempts: 10,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(350),
+        jitter: false,
+    };
+
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(350));
+}
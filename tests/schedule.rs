@@ -0,0 +1,150 @@
+use std::error::Error;
+
+use chrono::{NaiveTime, Weekday};
+use madpinger::search::query::SearchQuery;
+use madpinger::section::filter::{parse_time_of_day, Days, PackageFilter, ScheduleFilter};
+use madpinger::{CourseStatusFilters, CseClient};
+use serde_json::json;
+
+use common::{course, fixture, hit, packages, MockResponse, MockServer};
+
+mod common;
+
+fn time(h: u32, m: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(h, m, 0).unwrap()
+}
+
+/// The class numbers of the fixture packages that pass `schedule`.
+fn passing(schedule: ScheduleFilter) -> Vec<usize> {
+    PackageFilter::new()
+        .schedule(schedule)
+        .apply(packages())
+        .iter()
+        .map(|p| p.enrollment_class_number)
+        .collect()
+}
+
+#[test]
+fn days_parse_from_letters_or_names() -> Result<(), Box<dyn Error>> {
+    let tr: Days = "TR".parse()?;
+    assert!(tr.contains(Weekday::Tue) && tr.contains(Weekday::Thu));
+    assert_eq!("tue, thu".parse::<Days>()?, tr);
+    assert_eq!("MWF".parse::<Days>()?.to_string(), "MWF");
+    assert!("MXF".parse::<Days>().is_err());
+    Ok(())
+}
+
+#[test]
+fn times_of_day_parse() -> Result<(), Box<dyn Error>> {
+    assert_eq!(parse_time_of_day("13:20")?, time(13, 20));
+    assert_eq!(parse_time_of_day("1:20pm")?, time(13, 20));
+    assert_eq!(parse_time_of_day("11am")?, time(11, 0));
+    assert_eq!(parse_time_of_day("12am")?, time(0, 0));
+    assert!(parse_time_of_day("25:00").is_err());
+    Ok(())
+}
+
+#[test]
+fn meeting_times_are_read_in_campus_time() {
+    let lecture = &packages()[0].class_meetings[0];
+    assert_eq!(lecture.start_time(), Some(time(9, 55)));
+    assert_eq!(lecture.end_time(), Some(time(10, 45)));
+    assert_eq!(lecture.days().to_string(), "MWF");
+}
+
+#[test]
+fn every_class_meeting_has_to_fit() -> Result<(), Box<dyn Error>> {
+    assert_eq!(passing(ScheduleFilter::new()), [30002, 30004]);
+    assert_eq!(
+        passing(ScheduleFilter::new().not_days("R".parse()?)),
+        [30002]
+    );
+    assert_eq!(passing(ScheduleFilter::new().before(time(14, 0))), [30002]);
+    assert!(passing(ScheduleFilter::new().days("TR".parse()?)).is_empty());
+    // The final exam is in the evening, but it isn't a class meeting.
+    assert_eq!(passing(ScheduleFilter::new().after(time(9, 0))).len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_hits_are_checked_against_their_packages() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|req| {
+        if req.path.starts_with("/api/search/v1/enrollmentPackages/") {
+            MockResponse::ok(fixture("sections.json"))
+        } else {
+            MockResponse::ok(fixture("search.json"))
+        }
+    })
+    .await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+    let everything = SearchQuery::new("1242");
+
+    let fridays_off = PackageFilter::new().schedule(ScheduleFilter::new().not_days("F".parse()?));
+    assert!(client
+        .retain_with_packages(vec![course(hit())], &everything, &fridays_off, 4)
+        .await
+        .is_empty());
+
+    let thursdays_off = PackageFilter::new().schedule(ScheduleFilter::new().not_days("R".parse()?));
+    assert_eq!(
+        client
+            .retain_with_packages(vec![course(hit())], &everything, &thursdays_off, 4)
+            .await
+            .len(),
+        1
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn statuses_and_schedules_are_checked_on_the_same_package() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|_| MockResponse::ok(fixture("sections.json"))).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+    let open = SearchQuery::new("1242").statuses(CourseStatusFilters::new(true, false, false));
+    let waitlisted =
+        SearchQuery::new("1242").statuses(CourseStatusFilters::new(false, true, false));
+
+    // the open package meets on Tuesday; only the waitlisted one doesn't
+    let tuesdays_off = PackageFilter::new().schedule(ScheduleFilter::new().not_days("T".parse()?));
+    assert!(client
+        .retain_with_packages(vec![course(hit())], &open, &tuesdays_off, 4)
+        .await
+        .is_empty());
+    assert_eq!(
+        client
+            .retain_with_packages(vec![course(hit())], &waitlisted, &tuesdays_off, 4)
+            .await
+            .len(),
+        1
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn courses_whose_packages_fail_are_dropped() -> Result<(), Box<dyn Error>> {
+    let server = MockServer::start(|req| {
+        if req.path.ends_with("/999999") {
+            MockResponse::status(404)
+        } else {
+            MockResponse::ok(fixture("sections.json"))
+        }
+    })
+    .await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let mut broken = hit();
+    broken["courseId"] = json!("999999");
+
+    let thursdays_off = PackageFilter::new().schedule(ScheduleFilter::new().not_days("R".parse()?));
+    let kept = client
+        .retain_with_packages(
+            vec![course(hit()), course(broken)],
+            &SearchQuery::new("1242"),
+            &thursdays_off,
+            4,
+        )
+        .await;
+    let kept: Vec<&str> = kept.iter().map(|c| c.course_id.as_str()).collect();
+    assert_eq!(kept, ["024798"]);
+    Ok(())
+}
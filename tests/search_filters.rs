@@ -0,0 +1,142 @@
+use std::error::Error;
+
+use madpinger::search::query::{Breadth, Level, SearchQuery, SubjectFilter};
+use madpinger::search::schema::SearchedCourse;
+use madpinger::{CseClient, MadpingerError};
+use serde_json::{json, Value};
+
+use common::{hit, MockResponse, MockServer};
+
+mod common;
+
+/// The fixture hit, moved to another subject.
+fn hit_in(subject_code: &str, short_description: &str) -> Value {
+    let mut hit = hit();
+    hit["subject"]["subjectCode"] = json!(subject_code);
+    hit["subject"]["shortDescription"] = json!(short_description);
+    hit
+}
+
+/// Answers every search with one hit per subject.
+async fn catalog_server() -> MockServer {
+    MockServer::start(|_| {
+        let hits = vec![hit_in("266", "COMP SCI"), hit_in("600", "MATH")];
+        MockResponse::ok(json!({ "found": 2, "hits": hits }).to_string())
+    })
+    .await
+}
+
+#[test]
+fn subjects_are_parsed_as_codes_or_names() {
+    assert_eq!(
+        "266".parse::<SubjectFilter>().unwrap(),
+        SubjectFilter::Code("266".to_string())
+    );
+    assert_eq!(
+        "COMP SCI".parse::<SubjectFilter>().unwrap(),
+        SubjectFilter::Name("COMP SCI".to_string())
+    );
+}
+
+#[test]
+fn subject_codes_are_sent_to_the_api() -> Result<(), Box<dyn Error>> {
+    let query = SearchQuery::new("1242")
+        .subject(SubjectFilter::Code("266".to_string()))
+        .subject(SubjectFilter::Code("600".to_string()));
+
+    let payload = serde_json::to_value(&query)?;
+    assert_eq!(
+        payload["filters"][1],
+        json!({ "terms": { "subject.subjectCode": ["266", "600"] } })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn subject_names_are_resolved_to_codes() -> Result<(), Box<dyn Error>> {
+    let server = catalog_server().await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let subjects = vec![
+        SubjectFilter::Name("comp sci".to_string()),
+        SubjectFilter::Code("600".to_string()),
+    ];
+    let resolved = client.resolve_subjects("1242", subjects).await?;
+
+    assert_eq!(
+        resolved,
+        [
+            SubjectFilter::Code("266".to_string()),
+            SubjectFilter::Code("600".to_string())
+        ]
+    );
+    // only the name needed a search
+    assert_eq!(server.hits(), 1);
+    let body: Value = serde_json::from_str(&server.requests()[0].body)?;
+    assert_eq!(body["queryString"], "comp sci");
+
+    let err = client
+        .resolve_subjects("1242", vec![SubjectFilter::Name("ASTROLOGY".to_string())])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, MadpingerError::UnknownSubject { .. }));
+    Ok(())
+}
+
+#[tokio::test]
+async fn hits_outside_the_subjects_are_dropped() -> Result<(), Box<dyn Error>> {
+    let server = catalog_server().await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let query = SearchQuery::new("1242").subject(SubjectFilter::Name("MATH".to_string()));
+    let ping = client.run_query(&query).await?;
+
+    assert_eq!(ping.hits.len(), 1);
+    assert_eq!(ping.hits[0].subject.subject_code, "600");
+    Ok(())
+}
+
+#[test]
+fn requirements_parse_from_codes_or_descriptions() {
+    assert_eq!("N".parse::<Breadth>(), Ok(Breadth::NaturalScience));
+    assert_eq!(
+        "social science".parse::<Breadth>(),
+        Ok(Breadth::SocialScience)
+    );
+    assert_eq!("Advanced".parse::<Level>(), Ok(Level::Advanced));
+    assert!("X".parse::<Level>().is_err());
+}
+
+#[test]
+fn requirement_filters_are_sent_to_the_api() -> Result<(), Box<dyn Error>> {
+    let query = SearchQuery::new("1242")
+        .breadth(Breadth::NaturalScience)
+        .ethnic_studies(true)
+        .level(Level::Advanced);
+
+    let payload = serde_json::to_value(&query)?;
+    let filters = payload["filters"].as_array().unwrap();
+    assert!(filters.contains(&json!({ "terms": { "breadths.code": ["B", "N", "P"] } })));
+    assert!(filters.contains(&json!({ "exists": { "field": "ethnicStudies" } })));
+    assert!(filters.contains(&json!({ "terms": { "levels.code": ["A"] } })));
+    Ok(())
+}
+
+#[test]
+fn requirements_are_checked_against_courses() -> Result<(), Box<dyn Error>> {
+    let mut hit = hit_in("266", "COMP SCI");
+    hit["generalEd"] = json!({ "code": "COM B", "description": "Communication Part B" });
+    let course: SearchedCourse = serde_json::from_value(hit)?;
+
+    let query = SearchQuery::new("1242");
+    assert!(query.clone().general_ed("COM-B").matches(&course));
+    assert!(!query.clone().general_ed("QR-A").matches(&course));
+    assert!(query
+        .clone()
+        .breadth(Breadth::NaturalScience)
+        .matches(&course));
+    assert!(!query.clone().breadth(Breadth::Physical).matches(&course));
+    assert!(query.clone().level(Level::Intermediate).matches(&course));
+    assert!(!query.ethnic_studies(true).matches(&course));
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::error::Error;
+
+use futures::TryStreamExt;
+use madpinger::search::query::{SearchQuery, SortOrder};
+use madpinger::CseClient;
+use serde_json::{json, Value};
+
+use common::{hit, MockResponse, MockServer};
+
+mod common;
+
+/// Serves `found` copies of the fixture hit, paginated like the real API; later hits have titles
+/// earlier in the alphabet.
+async fn paginating_server(found: usize) -> MockServer {
+    let hit = hit();
+
+    MockServer::start(move |req| {
+        let body: Value = serde_json::from_str(&req.body).unwrap();
+        let page = body["page"].as_u64().unwrap() as usize;
+        let size = body["pageSize"].as_u64().unwrap() as usize;
+
+        let hits: Vec<Value> = ((page - 1) * size..(page * size).min(found))
+            .map(|i| {
+                let mut hit = hit.clone();
+                hit["courseId"] = json!(format!("{:06}", i));
+                hit["title"] = json!(format!("Course {:03}", found - i));
+                hit
+            })
+            .collect();
+        MockResponse::ok(json!({ "found": found, "hits": hits }).to_string())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn every_page_is_fetched() -> Result<(), Box<dyn Error>> {
+    let server = paginating_server(5).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let hits: Vec<_> = client
+        .search_all(SearchQuery::new("1242").page_size(2), None)
+        .try_collect()
+        .await?;
+
+    let ids: Vec<&str> = hits.iter().map(|h| h.course_id.as_str()).collect();
+    assert_eq!(ids, ["000000", "000001", "000002", "000003", "000004"]);
+    assert_eq!(server.hits(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn pagination_stops_at_the_limit() -> Result<(), Box<dyn Error>> {
+    let server = paginating_server(50).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+
+    let hits: Vec<_> = client
+        .search_all(SearchQuery::new("1242").page_size(2), Some(3))
+        .try_collect()
+        .await?;
+
+    assert_eq!(hits.len(), 3);
+    assert_eq!(server.hits(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn every_page_is_sorted_when_the_api_cant_sort() -> Result<(), Box<dyn Error>> {
+    let server = paginating_server(5).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+    let query = SearchQuery::new("1242")
+        .page_size(2)
+        .sort_order(SortOrder::Title);
+
+    let hits: Vec<_> = client.search_all(query, Some(4)).try_collect().await?;
+
+    let ids: Vec<&str> = hits.iter().map(|h| h.course_id.as_str()).collect();
+    assert_eq!(ids, ["000004", "000003", "000002", "000001"]);
+    assert_eq!(server.hits(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn api_sorted_pages_are_streamed_as_is() -> Result<(), Box<dyn Error>> {
+    let server = paginating_server(50).await;
+    let client = CseClient::builder().base_url(server.url()).build()?;
+    let query = SearchQuery::new("1242")
+        .page_size(2)
+        .sort_order(SortOrder::CatalogNumber);
+
+    let hits: Vec<_> = client.search_all(query, Some(3)).try_collect().await?;
+
+    let ids: Vec<&str> = hits.iter().map(|h| h.course_id.as_str()).collect();
+    assert_eq!(ids, ["000000", "000001", "000002"]);
+    assert_eq!(server.hits(), 2);
+    let body: Value = serde_json::from_str(&server.requests()[0].body)?;
+    assert_eq!(body["sortOrder"], "CATALOG_NUMBER");
+    Ok(())
+}
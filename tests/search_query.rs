@@ -0,0 +1,85 @@
+use std::error::Error;
+
+use madpinger::search::get_payload;
+use madpinger::search::query::{SearchQuery, SortOrder};
+use madpinger::search::schema::SearchedCourse;
+use madpinger::CourseStatusFilters;
+use serde_json::{json, Value};
+
+use common::{course, fixture, hit};
+
+mod common;
+
+#[test]
+fn query_matches_golden_json() -> Result<(), Box<dyn Error>> {
+    let query = SearchQuery::new("1242")
+        .query("calculus")
+        .statuses(CourseStatusFilters::new(true, true, false))
+        .page(2)
+        .page_size(25);
+
+    let golden: Value = serde_json::from_str(&fixture("search_query.json"))?;
+    assert_eq!(serde_json::to_value(&query)?, golden);
+    Ok(())
+}
+
+#[test]
+fn query_strings_are_escaped() -> Result<(), Box<dyn Error>> {
+    let search = r#"the "best" \ worst"#;
+    let payload = get_payload("1242", search, 10, CourseStatusFilters::default());
+
+    let parsed: Value = serde_json::from_str(&payload)?;
+    assert_eq!(parsed["queryString"], search);
+    Ok(())
+}
+
+#[test]
+fn no_statuses_means_any_status() -> Result<(), Box<dyn Error>> {
+    let query = SearchQuery::new("1242").statuses(CourseStatusFilters::new(false, false, false));
+
+    let payload = serde_json::to_value(&query)?;
+    let must = &payload["filters"][0]["has_child"]["query"]["bool"]["must"];
+    assert_eq!(must.as_array().map(Vec::len), Some(1));
+    Ok(())
+}
+
+#[test]
+fn sort_orders_are_sent_to_the_api_when_supported() -> Result<(), Box<dyn Error>> {
+    let payload = |order| serde_json::to_value(SearchQuery::new("1242").sort_order(order));
+
+    assert_eq!(payload(SortOrder::Subject)?["sortOrder"], "SUBJECT");
+    assert_eq!(
+        payload(SortOrder::CatalogNumber)?["sortOrder"],
+        "CATALOG_NUMBER"
+    );
+    assert_eq!(payload(SortOrder::LastTaught)?["sortOrder"], "LAST_TAUGHT");
+    assert_eq!(payload(SortOrder::Credits)?["sortOrder"], "SCORE");
+    assert_eq!(payload(SortOrder::Title)?["sortOrder"], "SCORE");
+    assert_eq!("catalog-number".parse(), Ok(SortOrder::CatalogNumber));
+    assert_eq!("relevance".parse(), Ok(SortOrder::Score));
+    assert!("popularity".parse::<SortOrder>().is_err());
+    Ok(())
+}
+
+#[test]
+fn client_side_sorts_reorder_courses() -> Result<(), Box<dyn Error>> {
+    let mut courses: Vec<SearchedCourse> = [("Zoology", 1), ("algebra", 4), ("Biology", 2)]
+        .iter()
+        .map(|(title, credits)| {
+            let mut hit = hit();
+            hit["title"] = json!(title);
+            hit["minimumCredits"] = json!(credits);
+            hit["maximumCredits"] = json!(credits);
+            course(hit)
+        })
+        .collect();
+    let titles = |courses: &[SearchedCourse]| -> Vec<String> {
+        courses.iter().map(|c| c.title.clone()).collect()
+    };
+
+    SortOrder::Title.sort(&mut courses);
+    assert_eq!(titles(&courses), ["algebra", "Biology", "Zoology"]);
+    SortOrder::Credits.sort(&mut courses);
+    assert_eq!(titles(&courses), ["Zoology", "Biology", "algebra"]);
+    Ok(())
+}
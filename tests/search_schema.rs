@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use madpinger::search::schema::{GradingBasis, Honors, SearchApiPing};
+use serde_json::{json, Value};
+
+use common::fixture;
+
+mod common;
+
+#[test]
+fn detailed_fields_are_modeled() -> Result<(), Box<dyn Error>> {
+    let ping: SearchApiPing = serde_json::from_str(&fixture("search_detailed.json"))?;
+    let course = &ping.hits[0];
+
+    assert_eq!(
+        course.grading_basis,
+        Some(GradingBasis {
+            code: "OPT".to_string(),
+            description: "Student Option".to_string()
+        })
+    );
+    assert_eq!(course.honors, Some(Honors::InstructorApproved));
+    assert_eq!(course.grad_course_work, Some(true));
+    assert_eq!(course.academic_group_code, ["L&S", "EGR"]);
+    assert_eq!(course.cross_listed_designations(), ["E C E 400"]);
+    assert_eq!(
+        course.advisory_prerequisites.as_deref(),
+        Some("COMP SCI 354 is recommended")
+    );
+    assert_eq!(
+        course.sustainability.as_ref().map(|s| s.code.as_str()),
+        Some("SR")
+    );
+    let content = course.instructor_provided_content.as_ref().unwrap();
+    assert_eq!(content.format.as_deref(), Some("Lecture and discussion"));
+    assert_eq!(
+        course.course_requirements.as_ref().map(|r| r.len()),
+        Some(2)
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_fields_are_tolerated() -> Result<(), Box<dyn Error>> {
+    let ping: SearchApiPing = serde_json::from_str(&fixture("search.json"))?;
+    let course = &ping.hits[0];
+
+    assert_eq!(course.grading_basis, None);
+    assert_eq!(course.honors, None);
+    assert!(course.all_cross_listed_subjects.is_empty());
+    assert!(course.cross_listed_designations().is_empty());
+    Ok(())
+}
+
+#[test]
+fn unexpected_shapes_fall_back_instead_of_failing() -> Result<(), Box<dyn Error>> {
+    let mut body: Value = serde_json::from_str(&fixture("search_detailed.json"))?;
+    let hit = &mut body["hits"][0];
+    hit["gradingBasis"] = json!("OPT");
+    hit["academicGroupCode"] = json!("L&S");
+    hit["honors"] = json!("HONORS_SOMEDAY");
+    hit["allCrossListedSubjects"][1] = json!({ "subjectCode": "347" });
+
+    let ping: SearchApiPing = serde_json::from_value(body)?;
+    let course = &ping.hits[0];
+    assert_eq!(course.grading_basis, None);
+    assert_eq!(course.academic_group_code, ["L&S"]);
+    assert_eq!(
+        course.honors,
+        Some(Honors::Other("HONORS_SOMEDAY".to_string()))
+    );
+    assert_eq!(course.all_cross_listed_subjects.len(), 1);
+    Ok(())
+}
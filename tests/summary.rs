@@ -0,0 +1,48 @@
+use madpinger::section::cross_listing::EnrollmentSource;
+use madpinger::section::schema::Status;
+use madpinger::summary::summarize_packages;
+
+use common::packages;
+
+mod common;
+
+#[test]
+fn packages_are_summarized() {
+    let summaries = summarize_packages(&packages());
+    assert_eq!(summaries.len(), 2);
+
+    let open = &summaries[0];
+    assert_eq!(open.designation(), "COMP SCI 400");
+    assert_eq!(open.components, ["LEC 001", "DIS 311"]);
+    assert_eq!(open.status, Status::Open);
+    assert_eq!(open.open_seats(), Some(8));
+    let enrollment = open.enrollment.unwrap();
+    assert_eq!((enrollment.enrolled, enrollment.capacity), (22, 30));
+    assert_eq!(enrollment.source, EnrollmentSource::Aggregate);
+    assert_eq!(open.meetings[0], "MWF 9:55–10:45 @ Van Vleck Hall B102");
+
+    let waitlisted = &summaries[1];
+    assert_eq!(waitlisted.status, Status::Waitlisted);
+    assert_eq!(waitlisted.open_seats(), Some(0));
+    assert_eq!(waitlisted.enrollment.unwrap().waitlisted, 3);
+    assert_eq!(
+        waitlisted.enrollment.unwrap().source,
+        EnrollmentSource::Fallback
+    );
+}
+
+#[test]
+fn packages_without_sections_or_enrollment_are_summarized() {
+    let mut packages = packages();
+    for package in &mut packages {
+        package.sections.clear();
+        package.enrollment_status = None;
+    }
+
+    let summaries = summarize_packages(&packages);
+    assert_eq!(summaries.len(), 2);
+    assert!(summaries[0].designations.is_empty());
+    assert!(summaries[0].components.is_empty());
+    assert_eq!(summaries[0].open_seats(), None);
+    assert_eq!(summaries[0].first_day, None);
+}
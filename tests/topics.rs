@@ -0,0 +1,61 @@
+use std::error::Error;
+
+use madpinger::search::query::SearchQuery;
+use madpinger::search::schema::SearchedCourse;
+use serde_json::json;
+
+use common::{course, hit, package_values, packages_from};
+
+mod common;
+
+fn topics_course() -> SearchedCourse {
+    let mut hit = hit();
+    hit["approvedForTopics"] = json!(true);
+    hit["topics"] = json!([
+        {
+            "shortDescription": "Building User Interfaces",
+            "longDescription": "Designing and implementing interactive interfaces.",
+            "id": 1,
+            "topicLastTaught": "1234"
+        },
+        {
+            "shortDescription": "Quantum Computing",
+            "longDescription": "Qubits, gates and algorithms.",
+            "id": 2,
+            "topicLastTaught": "1232"
+        }
+    ]);
+    course(hit)
+}
+
+#[test]
+fn section_topics_are_modeled() -> Result<(), Box<dyn Error>> {
+    let mut packages = package_values();
+    packages[0]["sections"][0]["topic"] = json!({
+        "id": 2,
+        "shortDescription": "Quantum Computing",
+        "longDescription": null
+    });
+    packages[1]["sections"][0]["topic"] = json!("not a topic");
+    let packages = packages_from(packages);
+
+    let topic = packages[0].topic().map(|t| t.short_description.as_str());
+    assert_eq!(topic, Some("Quantum Computing"));
+    assert_eq!(packages[1].topic(), None);
+    Ok(())
+}
+
+#[test]
+fn courses_are_matched_on_topic_descriptions() {
+    let course = topics_course();
+
+    let matching: Vec<&str> = course
+        .topics_matching("qubits")
+        .map(|t| t.short_description.as_str())
+        .collect();
+    assert_eq!(matching, ["Quantum Computing"]);
+    assert!(SearchQuery::new("1242")
+        .topic("interfaces")
+        .matches(&course));
+    assert!(!SearchQuery::new("1242").topic("databases").matches(&course));
+}
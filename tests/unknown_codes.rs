@@ -0,0 +1,55 @@
+use std::error::Error;
+
+use madpinger::section::schema::{AssemblyType, MeetingType, Status};
+use serde_json::json;
+
+use common::{package_values, packages_from};
+
+mod common;
+
+#[test]
+fn unknown_codes_are_kept() -> Result<(), Box<dyn Error>> {
+    let mut packages = package_values();
+    packages[0]["packageEnrollmentStatus"]["status"] = json!("CANCELLED");
+    packages[0]["sections"][1]["type"] = json!("CLN");
+    packages[0]["classMeetings"][0]["meetingType"] = json!("FIELD_TRIP");
+
+    let packages = packages_from(packages);
+    let package = &packages[0];
+    assert_eq!(
+        package.package_enrollment_status.status,
+        Status::Other("CANCELLED".to_string())
+    );
+    assert_eq!(
+        package.sections[1].assembly_type,
+        AssemblyType::Other("CLN".to_string())
+    );
+    assert_eq!(package.sections[1].component(), "CLN 311");
+    assert_eq!(
+        package.class_meetings[0].meeting_type,
+        MeetingType::Other("FIELD_TRIP".to_string())
+    );
+    assert_eq!(
+        packages[1].package_enrollment_status.status,
+        Status::Waitlisted
+    );
+    assert_eq!(packages[1].sections[0].assembly_type, AssemblyType::Lec);
+    Ok(())
+}
+
+#[test]
+fn codes_round_trip() -> Result<(), Box<dyn Error>> {
+    for code in ["OPEN", "CANCELLED"] {
+        let status: Status = serde_json::from_value(json!(code))?;
+        assert_eq!(serde_json::to_value(&status)?, json!(code));
+    }
+    for code in ["DIS", "CLS"] {
+        let assembly_type: AssemblyType = serde_json::from_value(json!(code))?;
+        assert_eq!(serde_json::to_value(&assembly_type)?, json!(code));
+    }
+    for code in ["EXAM", "FIELD_TRIP"] {
+        let meeting_type: MeetingType = serde_json::from_value(json!(code))?;
+        assert_eq!(serde_json::to_value(&meeting_type)?, json!(code));
+    }
+    Ok(())
+}